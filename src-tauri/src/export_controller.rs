@@ -0,0 +1,39 @@
+use crate::journal::JournalError;
+use crate::journal::{EntryWriter, ExportFormat};
+use crate::journal::{Journal, JournalSource};
+use crate::journal_controller::{JournalQuery, build_query, query_source};
+use std::fs::File;
+use std::io::BufWriter;
+use tauri::async_runtime::Mutex;
+
+#[tauri::command]
+#[instrument]
+pub(crate) async fn export_logs(
+    query: JournalQuery,
+    format: ExportFormat,
+    path: String,
+    source: tauri::State<'_, Mutex<JournalSource>>,
+) -> Result<u64, JournalError> {
+    debug!("Exporting logs to {} as {:?}...", path, format);
+
+    let fields = query.fields().to_vec();
+    let q = build_query(query)?;
+
+    let source = query_source(&*source.lock().await, &q);
+
+    // Reads the whole range and writes the file, away from the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        // Own handle rather than a session one, so the view can keep querying while exporting
+        let j = Journal::open_source(&source)?;
+        let file = File::create(&path)?;
+        let mut writer = EntryWriter::new(BufWriter::new(file), format, fields)?;
+        let count = j.export(&q, &mut writer)?;
+        writer.finish()?;
+
+        debug!("Exported {} entries to {}", count, path);
+
+        Ok(count)
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
@@ -0,0 +1,615 @@
+use super::journal_fields;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Fields whose values are hex encoded by the kernel when not quoted,
+/// as they may contain spaces or control characters
+const HEX_ENCODED_FIELDS: [&str; 8] = [
+    "proctitle",
+    "comm",
+    "exe",
+    "cwd",
+    "name",
+    "cmd",
+    "acct",
+    "data",
+];
+
+/// Record types logged when a user logs in or out, or authenticates
+const LOGIN_TYPES: [&str; 4] = ["LOGIN", "USER_LOGIN", "USER_LOGOUT", "USER_AUTH"];
+
+/// One line of an audit event, e.g. the `SYSCALL` or the `PATH` record
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    /// Name of the type, e.g. `AVC`, or its number if unknown
+    pub record_type: String,
+    /// Serial number shared by the records of an event, unique within a boot
+    pub serial: Option<u64>,
+    /// Time of the event in microseconds since the epoch
+    pub timestamp: Option<u64>,
+    /// Fields in the order of the record, with the hex encoded values decoded
+    pub fields: Vec<(String, String)>,
+}
+
+impl AuditRecord {
+    /// First value of the field
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Records sharing a serial number, with what they describe when it is known
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEvent {
+    pub serial: u64,
+    pub timestamp: u64,
+    pub details: AuditDetails,
+    /// Records in the order they were logged
+    pub records: Vec<AuditRecord>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AuditDetails {
+    /// Access checked by SELinux or AppArmor
+    Avc(AvcEvent),
+    /// Program started
+    Execve(ExecveEvent),
+    /// User logged in or out, or authenticated
+    Login(LoginEvent),
+    Other,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AvcEvent {
+    /// `denied` or `granted` for SELinux, `DENIED`, `ALLOWED` or `AUDIT` for AppArmor
+    pub result: String,
+    pub permissions: Vec<String>,
+    pub pid: Option<u32>,
+    pub comm: Option<String>,
+    pub name: Option<String>,
+    /// SELinux context or AppArmor profile of the process
+    pub source: Option<String>,
+    /// SELinux context of the object
+    pub target: Option<String>,
+    /// SELinux class of the object, e.g. `file`
+    pub target_class: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecveEvent {
+    pub argv: Vec<String>,
+    pub exe: Option<String>,
+    pub cwd: Option<String>,
+    pub pid: Option<u32>,
+    pub uid: Option<u32>,
+    /// Login user id, which stays the same after changing the user
+    pub auid: Option<u32>,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginEvent {
+    pub record_type: String,
+    /// Account name, or the user id if only that was logged
+    pub account: Option<String>,
+    pub exe: Option<String>,
+    pub hostname: Option<String>,
+    pub address: Option<String>,
+    pub terminal: Option<String>,
+    pub success: bool,
+}
+
+/// Parses the message of an audit entry, either as logged by the kernel,
+/// `type=AVC msg=audit(1700000000.123:456): ...` or `audit: type=1400 audit(...): ...`,
+/// or as stored by journald with the type name first, `AVC avc:  denied ...`
+pub fn parse_record(message: &str) -> AuditRecord {
+    let mut record = AuditRecord::default();
+    let mut body = message.strip_prefix("audit: ").unwrap_or(message);
+
+    let header = body
+        .find("audit(")
+        .and_then(|start| body[start..].find("):").map(|len| (start, start + len)));
+
+    if let Some((start, end)) = header {
+        if let Some((time, serial)) = body[start + "audit(".len()..end].split_once(':') {
+            record.timestamp = parse_time(time);
+            record.serial = serial.parse().ok();
+        }
+
+        record.record_type = body[..start]
+            .split_whitespace()
+            .find_map(|t| t.strip_prefix("type="))
+            .map(type_name)
+            .unwrap_or_default();
+        body = &body[end + "):".len()..];
+    } else if let Some((first, rest)) = body.split_once(' ') {
+        if is_type_name(first) {
+            record.record_type = first.to_owned();
+            body = rest;
+        }
+    }
+
+    parse_fields(body, &mut record.fields);
+
+    record
+}
+
+/// Fields of the entries of the audit transport read to build the records
+pub(crate) const AUDIT_FIELDS: [&str; 5] = [
+    journal_fields::MESSAGE,
+    journal_fields::AUDIT_ID,
+    journal_fields::AUDIT_TYPE_NAME,
+    journal_fields::SOURCE_REALTIME_TIMESTAMP,
+    journal_fields::REALTIME,
+];
+
+/// Record of a row with the `AUDIT_FIELDS`. journald moves the serial number and the time
+/// of the event out of the message, into `_AUDIT_ID` and `_SOURCE_REALTIME_TIMESTAMP`.
+pub(crate) fn record_from_row(row: &[String]) -> AuditRecord {
+    let field = |i: usize| row.get(i).map(String::as_str).unwrap_or_default();
+    let mut record = parse_record(field(0));
+
+    if record.serial.is_none() {
+        record.serial = field(1).parse().ok();
+    }
+    if !field(2).is_empty() {
+        record.record_type = field(2).to_owned();
+    }
+    if record.timestamp.is_none() {
+        record.timestamp = field(3).parse().or(field(4).parse()).ok();
+    }
+
+    record
+}
+
+/// Groups the records by serial number, keeping the order of the first record of each event
+pub fn group_events(records: Vec<AuditRecord>) -> Vec<AuditEvent> {
+    let mut events: Vec<AuditEvent> = vec![];
+    let mut by_serial: HashMap<(u64, u64), usize> = HashMap::new();
+
+    for record in records {
+        let serial = record.serial.unwrap_or_default();
+        let timestamp = record.timestamp.unwrap_or_default();
+
+        // Records without serial can't be related to others
+        let index = match record.serial {
+            Some(_) => by_serial.get(&(serial, timestamp)).copied(),
+            None => None,
+        };
+
+        match index {
+            Some(i) => events[i].records.push(record),
+            None => {
+                by_serial.insert((serial, timestamp), events.len());
+                events.push(AuditEvent {
+                    serial,
+                    timestamp,
+                    details: AuditDetails::Other,
+                    records: vec![record],
+                });
+            }
+        }
+    }
+
+    for event in events.iter_mut() {
+        event.records.sort_by_key(|r| record_order(&r.record_type));
+        event.details = details(&event.records);
+    }
+
+    events
+}
+
+/// The kernel logs the records of a syscall in this order, the rest follow
+fn record_order(record_type: &str) -> usize {
+    ["SYSCALL", "EXECVE", "CWD", "PATH", "PROCTITLE"]
+        .iter()
+        .position(|t| *t == record_type)
+        .unwrap_or(5)
+}
+
+fn details(records: &[AuditRecord]) -> AuditDetails {
+    let find = |record_type: &str| records.iter().find(|r| r.record_type == record_type);
+    let syscall = find("SYSCALL");
+
+    if let Some(avc) = records
+        .iter()
+        .find(|r| r.record_type == "AVC" || r.record_type == "USER_AVC")
+    {
+        let apparmor = avc.get("apparmor");
+        let permissions = match apparmor {
+            Some(_) => avc.get("denied_mask").or(avc.get("requested_mask")),
+            None => avc.get("permissions"),
+        };
+
+        return AuditDetails::Avc(AvcEvent {
+            result: apparmor
+                .or(avc.get("result"))
+                .unwrap_or_default()
+                .to_owned(),
+            permissions: permissions
+                .map(|p| p.split_whitespace().map(String::from).collect())
+                .unwrap_or_default(),
+            pid: number(avc, "pid"),
+            comm: text(avc, "comm"),
+            name: text(avc, "name"),
+            source: text(avc, "scontext").or(text(avc, "profile")),
+            target: text(avc, "tcontext"),
+            target_class: text(avc, "tclass"),
+        });
+    }
+
+    if let Some(execve) = find("EXECVE") {
+        let argc: usize = number(execve, "argc").unwrap_or_default();
+
+        return AuditDetails::Execve(ExecveEvent {
+            argv: (0..argc)
+                .map(|i| {
+                    execve
+                        .get(&format!("a{}", i))
+                        .unwrap_or_default()
+                        .to_owned()
+                })
+                .collect(),
+            exe: syscall.and_then(|s| text(s, "exe")),
+            cwd: find("CWD").and_then(|c| text(c, "cwd")),
+            pid: syscall.and_then(|s| number(s, "pid")),
+            uid: syscall.and_then(|s| number(s, "uid")),
+            auid: syscall.and_then(|s| number(s, "auid")),
+        });
+    }
+
+    if let Some(login) = records
+        .iter()
+        .find(|r| LOGIN_TYPES.contains(&r.record_type.as_str()))
+    {
+        return AuditDetails::Login(LoginEvent {
+            record_type: login.record_type.clone(),
+            account: text(login, "acct").or(text(login, "id")),
+            exe: text(login, "exe"),
+            hostname: text(login, "hostname"),
+            address: text(login, "addr"),
+            terminal: text(login, "terminal"),
+            success: matches!(login.get("res"), Some("success") | Some("1")),
+        });
+    }
+
+    AuditDetails::Other
+}
+
+/// Value of the field, leaving out the placeholders of missing values
+fn text(record: &AuditRecord, key: &str) -> Option<String> {
+    record
+        .get(key)
+        .filter(|v| !matches!(*v, "?" | "(null)" | "(none)"))
+        .map(String::from)
+}
+
+fn number<T: std::str::FromStr>(record: &AuditRecord, key: &str) -> Option<T> {
+    record.get(key).and_then(|v| v.parse().ok())
+}
+
+/// Splits `key=value` pairs, where values can be "double quoted", hex encoded or
+/// 'single quoted' with more pairs inside, like the `msg` of the records of user space.
+/// SELinux permissions in braces are kept as `permissions` and the result as `result`.
+fn parse_fields(text: &str, fields: &mut Vec<(String, String)>) {
+    let mut rest = text.trim_start();
+
+    while !rest.is_empty() {
+        if let Some(braced) = rest.strip_prefix('{') {
+            let end = braced.find('}').unwrap_or(braced.len());
+            fields.push(("permissions".into(), braced[..end].trim().to_owned()));
+            rest = braced.get(end + 1..).unwrap_or_default().trim_start();
+            continue;
+        }
+
+        let word_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=')
+            .unwrap_or(rest.len());
+        let key = &rest[..word_end];
+        rest = &rest[word_end..];
+
+        match rest.strip_prefix('=') {
+            Some(value) => {
+                let (value, remaining) = if let Some(quoted) = value.strip_prefix('"') {
+                    let end = quoted.find('"').unwrap_or(quoted.len());
+                    (Some(quoted[..end].to_owned()), quoted.get(end + 1..))
+                } else if let Some(quoted) = value.strip_prefix('\'') {
+                    let end = quoted.find('\'').unwrap_or(quoted.len());
+                    parse_fields(&quoted[..end], fields);
+                    (None, quoted.get(end + 1..))
+                } else {
+                    let end = value.find(char::is_whitespace).unwrap_or(value.len());
+                    (Some(decode_value(key, &value[..end])), value.get(end..))
+                };
+
+                if let Some(value) = value {
+                    fields.push((key.to_owned(), value));
+                }
+                rest = remaining.unwrap_or_default();
+            }
+            None => {
+                if key == "denied" || key == "granted" {
+                    fields.push(("result".into(), key.to_owned()));
+                }
+            }
+        }
+
+        rest = rest.trim_start();
+    }
+}
+
+/// Decodes the unquoted values of the fields the kernel hex encodes,
+/// the arguments of the command line are separated by NUL characters
+fn decode_value(key: &str, value: &str) -> String {
+    let is_encoded_field = HEX_ENCODED_FIELDS.contains(&key)
+        || key
+            .strip_prefix('a')
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+
+    // Checked on the bytes, as multi-byte characters can't be sliced in pairs
+    if !is_encoded_field
+        || value.len() < 2
+        || value.len() % 2 != 0
+        || !value.bytes().all(|b| b.is_ascii_hexdigit())
+    {
+        return value.to_owned();
+    }
+
+    let bytes: Vec<u8> = value
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| (hex_digit(pair[0]) << 4) | hex_digit(pair[1]))
+        .collect();
+
+    String::from_utf8_lossy(&bytes)
+        .trim_end_matches('\0')
+        .replace('\0', " ")
+}
+
+fn hex_digit(b: u8) -> u8 {
+    match b {
+        b'0'..=b'9' => b - b'0',
+        b'a'..=b'f' => b - b'a' + 10,
+        _ => b - b'A' + 10,
+    }
+}
+
+/// `1700000000.123` in microseconds
+fn parse_time(time: &str) -> Option<u64> {
+    let (seconds, millis) = time.split_once('.').unwrap_or((time, "0"));
+    let seconds: u64 = seconds.parse().ok()?;
+    let millis: u64 = millis.parse().ok()?;
+
+    seconds
+        .checked_mul(1_000_000)?
+        .checked_add(millis.checked_mul(1_000)?)
+}
+
+fn is_type_name(word: &str) -> bool {
+    word.len() > 1
+        && word
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'_')
+}
+
+/// Names of the record types of the events shown, see linux/audit.h
+fn type_name(record_type: &str) -> String {
+    let name = match record_type {
+        "1006" => "LOGIN",
+        "1100" => "USER_AUTH",
+        "1101" => "USER_ACCT",
+        "1105" => "USER_START",
+        "1106" => "USER_END",
+        "1107" => "USER_AVC",
+        "1112" => "USER_LOGIN",
+        "1113" => "USER_LOGOUT",
+        "1300" => "SYSCALL",
+        "1302" => "PATH",
+        "1307" => "CWD",
+        "1309" => "EXECVE",
+        "1327" => "PROCTITLE",
+        "1400" => "AVC",
+        other => other,
+    };
+
+    name.to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuditDetails, group_events, parse_record};
+    use crate::journal::QueryBuilder;
+    use crate::journal::testing::{entry, journal};
+
+    #[test]
+    fn kernel_and_journald_formats() {
+        let record = parse_record(
+            "audit: type=1400 audit(1700000000.123:456): apparmor=\"DENIED\" \
+             operation=\"open\" profile=\"snap.firefox\" name=\"/etc/shadow\" pid=2411 \
+             comm=\"firefox\" requested_mask=\"r\" denied_mask=\"r\"",
+        );
+        assert_eq!(record.record_type, "AVC");
+        assert_eq!(record.serial, Some(456));
+        assert_eq!(record.timestamp, Some(1_700_000_000_123_000));
+        assert_eq!(record.get("profile"), Some("snap.firefox"));
+        assert_eq!(record.get("comm"), Some("firefox"));
+
+        let record = parse_record(
+            "type=USER_LOGIN msg=audit(1700000001.005:77): pid=900 uid=0 auid=1000 \
+             msg='op=login id=1000 exe=\"/usr/sbin/sshd\" hostname=10.0.0.2 addr=10.0.0.2 \
+             terminal=ssh res=failed'",
+        );
+        assert_eq!(record.record_type, "USER_LOGIN");
+        assert_eq!(record.timestamp, Some(1_700_000_001_005_000));
+        assert_eq!(record.get("op"), Some("login"));
+        assert_eq!(record.get("exe"), Some("/usr/sbin/sshd"));
+        assert_eq!(record.get("res"), Some("failed"));
+        assert_eq!(record.get("msg"), None);
+
+        let record = parse_record("PROCTITLE proctitle=6C73002D6C61002F746D70");
+        assert_eq!(record.record_type, "PROCTITLE");
+        assert_eq!(record.serial, None);
+        assert_eq!(record.get("proctitle"), Some("ls -la /tmp"));
+    }
+
+    #[test]
+    fn hex_values_only_when_unquoted() {
+        let record = parse_record("SYSCALL comm=\"cafe\" exe=2F746D702F6D7920617070 pid=12");
+        assert_eq!(record.get("comm"), Some("cafe"));
+        assert_eq!(record.get("exe"), Some("/tmp/my app"));
+        assert_eq!(record.get("pid"), Some("12"));
+
+        // Not hex, kept as it is
+        let record = parse_record("SYSCALL comm=xyz name=(null)");
+        assert_eq!(record.get("comm"), Some("xyz"));
+        assert_eq!(record.get("name"), Some("(null)"));
+
+        // Nor are multi-byte characters split
+        let record = parse_record("SYSCALL comm=41aéb exe=1é1");
+        assert_eq!(record.get("comm"), Some("41aéb"));
+        assert_eq!(record.get("exe"), Some("1é1"));
+
+        // Nor are times out of range
+        let record = parse_record("type=SYSCALL msg=audit(99999999999999999.000:1): pid=1");
+        assert_eq!(record.timestamp, None);
+    }
+
+    #[test]
+    fn selinux_denial() {
+        let events = group_events(vec![parse_record(
+            "type=AVC msg=audit(1700000002.0:90): avc:  denied  { read write } for  pid=812 \
+             comm=\"httpd\" name=\"index.html\" dev=\"sda1\" ino=1234 \
+             scontext=system_u:system_r:httpd_t:s0 tcontext=unconfined_u:object_r:user_home_t:s0 \
+             tclass=file permissive=0",
+        )]);
+
+        let AuditDetails::Avc(avc) = &events[0].details else {
+            panic!("expected an AVC event");
+        };
+        assert_eq!(avc.result, "denied");
+        assert_eq!(avc.permissions, vec!["read", "write"]);
+        assert_eq!(avc.pid, Some(812));
+        assert_eq!(avc.comm.as_deref(), Some("httpd"));
+        assert_eq!(avc.source.as_deref(), Some("system_u:system_r:httpd_t:s0"));
+        assert_eq!(avc.target_class.as_deref(), Some("file"));
+    }
+
+    #[test]
+    fn execve_grouped_by_serial() {
+        // Newest first, as read from the journal
+        let records = vec![
+            parse_record("type=PROCTITLE msg=audit(1700000003.1:91): proctitle=6C73002D6C61"),
+            parse_record("type=CWD msg=audit(1700000003.1:91): cwd=\"/home/alice\""),
+            parse_record(
+                "type=USER_LOGIN msg=audit(1700000003.0:90): pid=900 uid=0 \
+                 msg='op=login acct=\"alice\" exe=\"/usr/sbin/sshd\" res=success'",
+            ),
+            parse_record("type=EXECVE msg=audit(1700000003.1:91): argc=2 a0=\"ls\" a1=\"-la\""),
+            parse_record(
+                "type=SYSCALL msg=audit(1700000003.1:91): arch=c000003e syscall=59 \
+                 success=yes pid=3001 auid=1000 uid=1000 comm=\"ls\" exe=\"/usr/bin/ls\"",
+            ),
+        ];
+
+        let events = group_events(records);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].serial, 91);
+
+        let types: Vec<&str> = events[0]
+            .records
+            .iter()
+            .map(|r| r.record_type.as_str())
+            .collect();
+        assert_eq!(types, vec!["SYSCALL", "EXECVE", "CWD", "PROCTITLE"]);
+
+        let AuditDetails::Execve(execve) = &events[0].details else {
+            panic!("expected an execve event");
+        };
+        assert_eq!(execve.argv, vec!["ls", "-la"]);
+        assert_eq!(execve.exe.as_deref(), Some("/usr/bin/ls"));
+        assert_eq!(execve.cwd.as_deref(), Some("/home/alice"));
+        assert_eq!(execve.pid, Some(3001));
+        assert_eq!(execve.auid, Some(1000));
+
+        let AuditDetails::Login(login) = &events[1].details else {
+            panic!("expected a login event");
+        };
+        assert_eq!(login.account.as_deref(), Some("alice"));
+        assert!(login.success);
+    }
+
+    #[test]
+    fn events_of_the_audit_transport() {
+        let logged = |i: u64, transport: &str, fields: &[(&str, &str)]| {
+            let mut all = vec![
+                ("_TRANSPORT", transport),
+                ("_SOURCE_REALTIME_TIMESTAMP", "1700000000000000"),
+            ];
+            all.extend_from_slice(fields);
+
+            entry(1_700_000_000_000_000 + i, &all)
+        };
+
+        // As stored by journald, without priority and with the serial number in a field
+        let j = journal(vec![
+            logged(
+                1,
+                "audit",
+                &[
+                    (
+                        "MESSAGE",
+                        "SYSCALL arch=c000003e syscall=59 pid=3001 exe=\"/usr/bin/id\"",
+                    ),
+                    ("_AUDIT_ID", "91"),
+                    ("_AUDIT_TYPE_NAME", "SYSCALL"),
+                ],
+            ),
+            logged(
+                2,
+                "audit",
+                &[
+                    ("MESSAGE", "EXECVE argc=1 a0=6964"),
+                    ("_AUDIT_ID", "91"),
+                    ("_AUDIT_TYPE_NAME", "EXECVE"),
+                ],
+            ),
+            logged(3, "journal", &[("MESSAGE", "hello"), ("PRIORITY", "6")]),
+            logged(
+                4,
+                "audit",
+                &[
+                    (
+                        "MESSAGE",
+                        "SERVICE_START pid=1 uid=0 msg='unit=sshd res=success'",
+                    ),
+                    ("_AUDIT_ID", "92"),
+                    ("_AUDIT_TYPE_NAME", "SERVICE_START"),
+                ],
+            ),
+        ]);
+
+        let q = QueryBuilder::default().with_audit().build();
+        let events = j.list_audit_events(&q).unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].serial, 92);
+        assert_eq!(events[0].details, AuditDetails::Other);
+        assert_eq!(events[0].records[0].get("unit"), Some("sshd"));
+
+        assert_eq!(events[1].serial, 91);
+        assert_eq!(events[1].timestamp, 1_700_000_000_000_000);
+        let AuditDetails::Execve(execve) = &events[1].details else {
+            panic!("expected an execve event");
+        };
+        assert_eq!(execve.argv, vec!["id"]);
+        assert_eq!(execve.exe.as_deref(), Some("/usr/bin/id"));
+    }
+}
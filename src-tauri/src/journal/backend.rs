@@ -0,0 +1,386 @@
+use super::error::JournalError;
+#[cfg(feature = "libsystemd")]
+use super::libsdjournal::*;
+use super::memory_journal::MemoryJournal;
+#[cfg(feature = "libsystemd")]
+use libc::c_void;
+
+/// Operations on a journal the query engine is built on, following sd-journal(3).
+/// Matches are combined like in libsystemd: matches of the same field are ORed,
+/// of different fields ANDed, and disjunctions and conjunctions group them on top.
+pub trait JournalBackend {
+    /// Adds a `FIELD=value` match
+    fn add_match(&self, m: &str) -> Result<(), JournalError>;
+    fn add_disjunction(&self) -> Result<(), JournalError>;
+    fn add_conjunction(&self) -> Result<(), JournalError>;
+    fn flush_matches(&self);
+
+    fn seek_head(&self) -> Result<(), JournalError>;
+    fn seek_tail(&self) -> Result<(), JournalError>;
+    fn seek_realtime_usec(&self, microseconds: u64) -> Result<(), JournalError>;
+    fn seek_cursor(&self, cursor: &str) -> Result<(), JournalError>;
+    /// Whether the current entry is the one of the cursor
+    fn test_cursor(&self, cursor: &str) -> Result<bool, JournalError>;
+
+    /// Moves to the next entry matching, returns false at the end
+    fn next(&self) -> Result<bool, JournalError>;
+    /// Moves to the previous entry matching, returns false at the start
+    fn previous(&self) -> Result<bool, JournalError>;
+
+    fn get_realtime_usec(&self) -> Result<u64, JournalError>;
+    /// Microseconds since the boot the current entry was logged in
+    fn get_monotonic_usec(&self) -> Result<u64, JournalError>;
+    fn get_cursor(&self) -> Result<String, JournalError>;
+    /// Value of the field in the current entry, without the field name
+    fn get_data(&self, field: &str) -> Result<String, JournalError>;
+    /// Every field of the current entry, with the values as raw bytes
+    fn enumerate_data(&self) -> Result<Vec<(String, Vec<u8>)>, JournalError>;
+    /// Catalog text of the message id of the current entry, with its fields filled in
+    fn get_catalog(&self) -> Result<Option<String>, JournalError>;
+
+    /// Every field name used in the journal
+    fn enumerate_fields(&self) -> Result<Vec<String>, JournalError>;
+    /// Distinct values of a field across the journal, regardless of the matches
+    fn query_unique(&self, field: &str) -> Result<Vec<String>, JournalError>;
+}
+
+/// Type of change reported by sd_journal_wait and sd_journal_process
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WakeupEvent {
+    /// The journal did not change since the last invocation
+    Nop,
+    /// New entries have been appended to the end of the journal
+    Append,
+    /// Journal files were added or removed, e.g. after rotation
+    Invalidate,
+}
+
+/// Journal read through libsystemd
+#[cfg(feature = "libsystemd")]
+#[derive(Debug)]
+pub struct SdJournal {
+    ptr: *mut c_void,
+}
+
+#[cfg(feature = "libsystemd")]
+impl SdJournal {
+    pub fn open(flags: u32) -> Result<SdJournal, JournalError> {
+        let mut journal = SdJournal::new();
+        sd_journal_open(&mut journal.ptr, flags)?;
+
+        Ok(journal)
+    }
+
+    /// Opens the journal of a namespace, the flags select more namespaces like
+    /// `SD_JOURNAL_ALL_NAMESPACES`
+    pub fn open_namespace(namespace: Option<&str>, flags: u32) -> Result<SdJournal, JournalError> {
+        let mut journal = SdJournal::new();
+        sd_journal_open_namespace(&mut journal.ptr, namespace, flags)?;
+
+        Ok(journal)
+    }
+
+    pub fn open_directory(path: &str) -> Result<SdJournal, JournalError> {
+        let mut journal = SdJournal::new();
+        sd_journal_open_directory(&mut journal.ptr, path, 0)?;
+
+        Ok(journal)
+    }
+
+    pub fn open_files(paths: &[String]) -> Result<SdJournal, JournalError> {
+        let mut journal = SdJournal::new();
+        sd_journal_open_files(&mut journal.ptr, paths, 0)?;
+
+        Ok(journal)
+    }
+
+    fn new() -> SdJournal {
+        SdJournal {
+            ptr: std::ptr::null_mut(),
+        }
+    }
+
+    pub fn get_fd(&self) -> Result<i32, JournalError> {
+        sd_journal_get_fd(self.ptr)
+    }
+
+    pub fn wait(&self, timeout_usec: u64) -> Result<WakeupEvent, JournalError> {
+        sd_journal_wait(self.ptr, timeout_usec)
+    }
+
+    /// Bytes on disk used by the journal files that are open
+    pub fn get_usage(&self) -> Result<u64, JournalError> {
+        sd_journal_get_usage(self.ptr)
+    }
+}
+
+#[cfg(feature = "libsystemd")]
+impl JournalBackend for SdJournal {
+    fn add_match(&self, m: &str) -> Result<(), JournalError> {
+        sd_journal_add_match(self.ptr, m.to_owned())
+    }
+
+    fn add_disjunction(&self) -> Result<(), JournalError> {
+        sd_journal_add_disjunction(self.ptr)
+    }
+
+    fn add_conjunction(&self) -> Result<(), JournalError> {
+        sd_journal_add_conjunction(self.ptr)
+    }
+
+    fn flush_matches(&self) {
+        sd_journal_flush_matches(self.ptr);
+    }
+
+    fn seek_head(&self) -> Result<(), JournalError> {
+        sd_journal_seek_head(self.ptr)
+    }
+
+    fn seek_tail(&self) -> Result<(), JournalError> {
+        sd_journal_seek_tail(self.ptr)
+    }
+
+    fn seek_realtime_usec(&self, microseconds: u64) -> Result<(), JournalError> {
+        sd_journal_seek_realtime_usec(self.ptr, microseconds)
+    }
+
+    fn seek_cursor(&self, cursor: &str) -> Result<(), JournalError> {
+        sd_journal_seek_cursor(self.ptr, cursor)
+    }
+
+    fn test_cursor(&self, cursor: &str) -> Result<bool, JournalError> {
+        sd_journal_test_cursor(self.ptr, cursor)
+    }
+
+    fn next(&self) -> Result<bool, JournalError> {
+        sd_journal_next(self.ptr)
+    }
+
+    fn previous(&self) -> Result<bool, JournalError> {
+        sd_journal_previous(self.ptr)
+    }
+
+    fn get_realtime_usec(&self) -> Result<u64, JournalError> {
+        let mut realtime: u64 = 0;
+        sd_journal_get_realtime_usec(self.ptr, &mut realtime)?;
+
+        Ok(realtime)
+    }
+
+    fn get_monotonic_usec(&self) -> Result<u64, JournalError> {
+        let mut monotonic: u64 = 0;
+        sd_journal_get_monotonic_usec(self.ptr, &mut monotonic)?;
+
+        Ok(monotonic)
+    }
+
+    fn get_cursor(&self) -> Result<String, JournalError> {
+        sd_journal_get_cursor(self.ptr)
+    }
+
+    fn get_data(&self, field: &str) -> Result<String, JournalError> {
+        sd_journal_get_data(self.ptr, field)
+    }
+
+    fn enumerate_data(&self) -> Result<Vec<(String, Vec<u8>)>, JournalError> {
+        sd_journal_restart_data(self.ptr);
+
+        let mut fields = vec![];
+        loop {
+            match sd_journal_enumerate_data(self.ptr) {
+                Ok(Some(field)) => fields.push(field),
+                Ok(None) => {}
+                Err(JournalError::EndOfFile) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(fields)
+    }
+
+    fn get_catalog(&self) -> Result<Option<String>, JournalError> {
+        sd_journal_get_catalog(self.ptr)
+    }
+
+    fn enumerate_fields(&self) -> Result<Vec<String>, JournalError> {
+        sd_journal_restart_fields(self.ptr);
+
+        let mut names = vec![];
+        loop {
+            match sd_journal_enumerate_fields(self.ptr) {
+                Ok(name) => names.push(name),
+                Err(JournalError::EndOfFile) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(names)
+    }
+
+    fn query_unique(&self, field: &str) -> Result<Vec<String>, JournalError> {
+        sd_journal_query_unique(self.ptr, field)?;
+        sd_journal_restart_unique(self.ptr);
+
+        let mut values = vec![];
+        loop {
+            match sd_journal_enumerate_unique(self.ptr, field) {
+                Ok(value) => values.push(value),
+                Err(JournalError::EndOfFile) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(values)
+    }
+}
+
+#[cfg(feature = "libsystemd")]
+impl Drop for SdJournal {
+    fn drop(&mut self) {
+        warn!("Dropping the journal");
+        sd_journal_close(self.ptr);
+    }
+}
+
+#[cfg(feature = "libsystemd")]
+unsafe impl Send for SdJournal {}
+
+/// Journal opened on a `JournalSource`: the files read through libsystemd,
+/// or an imported dump read into memory. Without libsystemd the journal files
+/// are read into memory too.
+#[derive(Debug)]
+pub enum SourceBackend {
+    #[cfg(feature = "libsystemd")]
+    Sd(SdJournal),
+    Memory(MemoryJournal),
+}
+
+macro_rules! delegate {
+    ($self:ident, $backend:ident => $call:expr) => {
+        match $self {
+            #[cfg(feature = "libsystemd")]
+            SourceBackend::Sd($backend) => $call,
+            SourceBackend::Memory($backend) => $call,
+        }
+    };
+}
+
+impl SourceBackend {
+    /// Whether the entries were read into memory, they don't change afterwards
+    pub fn is_in_memory(&self) -> bool {
+        matches!(self, SourceBackend::Memory(_))
+    }
+}
+
+#[cfg(feature = "libsystemd")]
+impl SourceBackend {
+    pub fn get_fd(&self) -> Result<i32, JournalError> {
+        self.sd()?.get_fd()
+    }
+
+    pub fn wait(&self, timeout_usec: u64) -> Result<WakeupEvent, JournalError> {
+        self.sd()?.wait(timeout_usec)
+    }
+
+    pub fn get_usage(&self) -> Result<u64, JournalError> {
+        self.sd()?.get_usage()
+    }
+
+    fn sd(&self) -> Result<&SdJournal, JournalError> {
+        match self {
+            SourceBackend::Sd(journal) => Ok(journal),
+            SourceBackend::Memory(_) => Err(JournalError::Internal(-libc::EOPNOTSUPP)),
+        }
+    }
+}
+
+/// Without libsystemd nothing tells when the files change, journals read into memory
+/// can't be followed
+#[cfg(not(feature = "libsystemd"))]
+impl SourceBackend {
+    pub fn get_fd(&self) -> Result<i32, JournalError> {
+        Err(JournalError::Internal(-libc::EOPNOTSUPP))
+    }
+
+    pub fn wait(&self, _timeout_usec: u64) -> Result<WakeupEvent, JournalError> {
+        Err(JournalError::Internal(-libc::EOPNOTSUPP))
+    }
+}
+
+impl JournalBackend for SourceBackend {
+    fn add_match(&self, m: &str) -> Result<(), JournalError> {
+        delegate!(self, b => b.add_match(m))
+    }
+
+    fn add_disjunction(&self) -> Result<(), JournalError> {
+        delegate!(self, b => b.add_disjunction())
+    }
+
+    fn add_conjunction(&self) -> Result<(), JournalError> {
+        delegate!(self, b => b.add_conjunction())
+    }
+
+    fn flush_matches(&self) {
+        delegate!(self, b => b.flush_matches())
+    }
+
+    fn seek_head(&self) -> Result<(), JournalError> {
+        delegate!(self, b => b.seek_head())
+    }
+
+    fn seek_tail(&self) -> Result<(), JournalError> {
+        delegate!(self, b => b.seek_tail())
+    }
+
+    fn seek_realtime_usec(&self, microseconds: u64) -> Result<(), JournalError> {
+        delegate!(self, b => b.seek_realtime_usec(microseconds))
+    }
+
+    fn seek_cursor(&self, cursor: &str) -> Result<(), JournalError> {
+        delegate!(self, b => b.seek_cursor(cursor))
+    }
+
+    fn test_cursor(&self, cursor: &str) -> Result<bool, JournalError> {
+        delegate!(self, b => b.test_cursor(cursor))
+    }
+
+    fn next(&self) -> Result<bool, JournalError> {
+        delegate!(self, b => b.next())
+    }
+
+    fn previous(&self) -> Result<bool, JournalError> {
+        delegate!(self, b => b.previous())
+    }
+
+    fn get_realtime_usec(&self) -> Result<u64, JournalError> {
+        delegate!(self, b => b.get_realtime_usec())
+    }
+
+    fn get_monotonic_usec(&self) -> Result<u64, JournalError> {
+        delegate!(self, b => b.get_monotonic_usec())
+    }
+
+    fn get_cursor(&self) -> Result<String, JournalError> {
+        delegate!(self, b => b.get_cursor())
+    }
+
+    fn get_data(&self, field: &str) -> Result<String, JournalError> {
+        delegate!(self, b => b.get_data(field))
+    }
+
+    fn enumerate_data(&self) -> Result<Vec<(String, Vec<u8>)>, JournalError> {
+        delegate!(self, b => b.enumerate_data())
+    }
+
+    fn get_catalog(&self) -> Result<Option<String>, JournalError> {
+        delegate!(self, b => b.get_catalog())
+    }
+
+    fn enumerate_fields(&self) -> Result<Vec<String>, JournalError> {
+        delegate!(self, b => b.enumerate_fields())
+    }
+
+    fn query_unique(&self, field: &str) -> Result<Vec<String>, JournalError> {
+        delegate!(self, b => b.query_unique(field))
+    }
+}
@@ -2,8 +2,55 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Boot {
-    index: i32,
-    boot_id: String,
-    first_entry: i64,
-    last_entry: i64,
+    pub index: i32,
+    pub boot_id: String,
+    pub first_entry: i64,
+    pub last_entry: i64,
+    /// Only counted on request by `Journal::count_boot_entries`, as it reads the whole boot
+    #[serde(default)]
+    pub entry_count: Option<u64>,
+    #[serde(default)]
+    pub kernel_version: Option<String>,
+    #[serde(default)]
+    pub hostname: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::journal::testing::{entry, journal};
+
+    #[test]
+    fn boots_with_their_range_and_entries_counted_on_request() {
+        let j = journal(vec![
+            entry(1, &[("_BOOT_ID", "b1"), ("_HOSTNAME", "old")]),
+            entry(2, &[("_BOOT_ID", "b1")]),
+            entry(
+                3,
+                &[
+                    ("_BOOT_ID", "b2"),
+                    ("_HOSTNAME", "web"),
+                    ("_TRANSPORT", "kernel"),
+                    ("MESSAGE", "Linux version 6.8.0-45-generic (buildd@lcy02)"),
+                ],
+            ),
+            entry(4, &[("_BOOT_ID", "b2")]),
+            entry(5, &[("_BOOT_ID", "b2")]),
+        ]);
+
+        let boots = j.list_boots().unwrap();
+        assert_eq!(boots.len(), 2);
+
+        assert_eq!(boots[0].index, 0);
+        assert_eq!(boots[0].boot_id, "b2");
+        assert_eq!((boots[0].first_entry, boots[0].last_entry), (3, 5));
+        assert_eq!(boots[0].kernel_version.as_deref(), Some("6.8.0-45-generic"));
+        assert_eq!(boots[0].hostname.as_deref(), Some("web"));
+        assert_eq!(boots[0].entry_count, None);
+
+        assert_eq!(boots[1].index, -1);
+        assert_eq!((boots[1].first_entry, boots[1].last_entry), (1, 2));
+
+        assert_eq!(j.count_boot_entries("b2").unwrap(), 3);
+        assert_eq!(j.count_boot_entries("missing").unwrap(), 0);
+    }
 }
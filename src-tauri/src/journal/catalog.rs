@@ -0,0 +1,317 @@
+use super::error::JournalError;
+use serde::Serialize;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// Compiled by `journalctl --update-catalog` from the `*.catalog` files, libsystemd looks
+/// message ids up in it
+const DATABASE: &str = "var/lib/systemd/catalog/database";
+const SIGNATURE: &[u8; 8] = b"RHHHKSLP";
+const HEADER_SIZE: usize = 40;
+/// 16 bytes of id, 32 of language and 8 of offset
+const ITEM_SIZE: usize = 56;
+
+/// Message id with an explanation in the catalog
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogEntry {
+    pub message_id: String,
+    /// Project the message comes from, e.g. `systemd`
+    pub component: Option<String>,
+    pub subject: String,
+}
+
+/// Text of a message id in one language, `""` for the text of the catalog files without one
+#[derive(Debug)]
+struct CatalogText {
+    message_id: String,
+    language: String,
+    text: String,
+}
+
+/// Message ids of the catalog under `root`, `/` for the running system, with their subjects
+/// in the language of the messages. libsystemd has no call to enumerate the catalog.
+pub(crate) fn list_catalog(root: &str) -> Vec<CatalogEntry> {
+    match read_database(root) {
+        Ok(texts) => catalog_entries(&texts, &messages_language()),
+        Err(e) => {
+            warn!("Could not read the message catalog: {}", e);
+            vec![]
+        }
+    }
+}
+
+/// Text of the catalog under `root` for a message id like `fc2e22bc6ee647b6b90729ab34a250b1`,
+/// in the language of the messages, with the `@FIELD@` placeholders left as they are.
+/// None if the id is not in the catalog.
+pub(crate) fn get_catalog_text(
+    root: &str,
+    message_id: &str,
+) -> Result<Option<String>, JournalError> {
+    let texts = read_database(root)?;
+    Ok(find_text(&texts, message_id, &messages_language()).map(|t| t.text.clone()))
+}
+
+/// A message id is listed once, with the `Subject:` and `Defined-By:` of its text
+fn catalog_entries(texts: &[CatalogText], language: &str) -> Vec<CatalogEntry> {
+    let mut entries: Vec<CatalogEntry> = vec![];
+
+    // The texts are sorted by message id, then language
+    for group in texts.chunk_by(|a, b| a.message_id == b.message_id) {
+        let Some(text) = pick_language(group, language) else {
+            continue;
+        };
+
+        let (mut component, mut subject) = (None, None);
+        for line in text.text.lines().take_while(|l| !l.is_empty()) {
+            if let Some(value) = line.strip_prefix("Subject:") {
+                subject = Some(value.trim().to_owned());
+            } else if let Some(value) = line.strip_prefix("Defined-By:") {
+                component = Some(value.trim().to_owned());
+            }
+        }
+
+        entries.push(CatalogEntry {
+            message_id: text.message_id.clone(),
+            component,
+            subject: subject.unwrap_or_default(),
+        });
+    }
+
+    entries
+}
+
+fn find_text<'a>(
+    texts: &'a [CatalogText],
+    message_id: &str,
+    language: &str,
+) -> Option<&'a CatalogText> {
+    let message_id = message_id.to_ascii_lowercase();
+    let start = texts.partition_point(|t| t.message_id < message_id);
+    let end = start + texts[start..].partition_point(|t| t.message_id == message_id);
+
+    pick_language(&texts[start..end], language)
+}
+
+/// Every text of the database, empty when the catalog was never compiled
+fn read_database(root: &str) -> Result<Vec<CatalogText>, JournalError> {
+    match std::fs::read(Path::new(root).join(DATABASE)) {
+        Ok(data) => parse_database(&data),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// A header with the number and size of the items, the items sorted by message id and
+/// language, then the NUL terminated texts they point to
+fn parse_database(data: &[u8]) -> Result<Vec<CatalogText>, JournalError> {
+    let invalid = || JournalError::InvalidFile("not a message catalog database".to_owned());
+    let read_u64 =
+        |bytes: &[u8]| usize::try_from(u64::from_le_bytes(bytes.try_into().unwrap())).ok();
+
+    if data.len() < HEADER_SIZE || !data.starts_with(SIGNATURE) {
+        return Err(invalid());
+    }
+
+    let header_size = read_u64(&data[16..24]).ok_or_else(invalid)?;
+    let n_items = read_u64(&data[24..32]).ok_or_else(invalid)?;
+    let item_size = read_u64(&data[32..40]).ok_or_else(invalid)?;
+    if header_size < HEADER_SIZE || item_size < ITEM_SIZE {
+        return Err(invalid());
+    }
+
+    let texts_start = n_items
+        .checked_mul(item_size)
+        .and_then(|size| size.checked_add(header_size))
+        .filter(|start| *start <= data.len())
+        .ok_or_else(invalid)?;
+
+    let mut texts = Vec::with_capacity(n_items);
+    for item in data[header_size..texts_start].chunks_exact(item_size) {
+        let offset = read_u64(&item[48..56])
+            .and_then(|offset| offset.checked_add(texts_start))
+            .filter(|offset| *offset < data.len())
+            .ok_or_else(invalid)?;
+
+        texts.push(CatalogText {
+            message_id: item[..16].iter().map(|b| format!("{:02x}", b)).collect(),
+            language: nul_terminated(&item[16..48]),
+            text: nul_terminated(&data[offset..]),
+        });
+    }
+
+    Ok(texts)
+}
+
+fn nul_terminated(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+/// Language of the messages as libsystemd picks it: `de_DE` of `de_DE.UTF-8`, empty for
+/// the `C` locale
+fn messages_language() -> String {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|v| !v.is_empty()))
+        .unwrap_or_default();
+
+    let language = &locale[..locale.find(['.', '@']).unwrap_or(locale.len())];
+    match language {
+        "C" | "POSIX" => String::new(),
+        _ => language.to_owned(),
+    }
+}
+
+/// The text of a message id in the language, else in its language without the territory,
+/// else the text without a language. A message id with translations only has no text.
+fn pick_language<'a>(texts: &'a [CatalogText], language: &str) -> Option<&'a CatalogText> {
+    let without_territory = language.split('_').next().unwrap_or_default();
+
+    [language, without_territory, ""]
+        .iter()
+        .find_map(|l| texts.iter().find(|t| t.language == *l))
+}
+
+/// Replaces the `@FIELD@` placeholders of a catalog text with the values of the entry,
+/// like sd_journal_get_catalog. Fields missing from the entry are replaced by nothing.
+pub(crate) fn replace_fields<F>(text: &str, mut get_field: F) -> String
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('@') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        let name = after.find('@').map(|end| &after[..end]).filter(|name| {
+            !name.is_empty()
+                && name
+                    .bytes()
+                    .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'_')
+        });
+
+        match name {
+            Some(name) => {
+                result.push_str(&get_field(name).unwrap_or_default());
+                rest = &after[name.len() + 1..];
+            }
+            None => {
+                result.push('@');
+                rest = after;
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CatalogEntry, catalog_entries, find_text, parse_database, read_database, replace_fields,
+    };
+    use crate::journal::JournalError;
+    use crate::journal::testing::{entry, journal};
+
+    const ROOT: &str = "./tests/fixtures/catalog";
+
+    #[test]
+    fn lists_message_ids_once() {
+        let texts = read_database(ROOT).unwrap();
+
+        // The German only message id has no text in the default language
+        assert_eq!(
+            catalog_entries(&texts, ""),
+            vec![
+                CatalogEntry {
+                    message_id: "f77379a8490b408bbe5f6940505a777b".into(),
+                    component: None,
+                    subject: "Journal started: Runtime journal".into(),
+                },
+                CatalogEntry {
+                    message_id: "fc2e22bc6ee647b6b90729ab34a250b1".into(),
+                    component: Some("systemd".into()),
+                    subject: "Process @COREDUMP_PID@ (@COREDUMP_COMM@) dumped core".into(),
+                },
+            ]
+        );
+
+        let subjects: Vec<String> = catalog_entries(&texts, "de_DE")
+            .into_iter()
+            .map(|e| e.subject)
+            .collect();
+        assert_eq!(
+            subjects,
+            vec![
+                "Nachrichten konnten nicht an syslog weitergeleitet werden",
+                "Journal started: Runtime journal",
+                "Prozess @COREDUMP_PID@ (@COREDUMP_COMM@) hat einen Speicherabzug erzeugt",
+            ]
+        );
+    }
+
+    #[test]
+    fn text_of_a_message_id() {
+        let texts = read_database(ROOT).unwrap();
+
+        let text = find_text(&texts, "FC2E22BC6EE647B6B90729AB34A250B1", "en_US").unwrap();
+        assert_eq!(text.language, "");
+        assert!(text.text.ends_with("crashed and dumped core.\n"));
+
+        let text = find_text(&texts, "fc2e22bc6ee647b6b90729ab34a250b1", "de_AT").unwrap();
+        assert_eq!(text.language, "de");
+
+        assert!(find_text(&texts, "0027229ca0644181a76c4e92458afa2e", "").is_none());
+        assert!(find_text(&texts, "not an id", "").is_none());
+    }
+
+    #[test]
+    fn missing_or_invalid_database() {
+        assert!(
+            read_database("./tests/fixtures/journald")
+                .unwrap()
+                .is_empty()
+        );
+        assert!(matches!(
+            parse_database(b"RHHHKSLP\0\0\0\0"),
+            Err(JournalError::InvalidFile(_))
+        ));
+    }
+
+    #[test]
+    fn replaces_the_fields_of_the_entry() {
+        let get_field = |field: &str| match field {
+            "COREDUMP_PID" => Some("2411".to_owned()),
+            "COREDUMP_COMM" => Some("nginx".to_owned()),
+            _ => None,
+        };
+
+        assert_eq!(
+            replace_fields(
+                "Process @COREDUMP_PID@ (@COREDUMP_COMM@) dumped core",
+                get_field
+            ),
+            "Process 2411 (nginx) dumped core"
+        );
+        assert_eq!(
+            replace_fields("Unit @UNIT@ failed, see user@host.", get_field),
+            "Unit  failed, see user@host."
+        );
+        assert_eq!(replace_fields("@ @lower@ @", get_field), "@ @lower@ @");
+    }
+
+    #[test]
+    fn entries_without_catalog() {
+        let j = journal(vec![entry(
+            1,
+            &[("MESSAGE", "hello"), ("MESSAGE_ID", "not an id")],
+        )]);
+
+        let entry = j.get_full_entry("c1").unwrap();
+        assert_eq!(entry.catalog, None);
+    }
+}
@@ -0,0 +1,215 @@
+use super::{Query, QueryBuilder, journal_fields};
+use serde::Serialize;
+
+const COREDUMP_EXE: &str = "COREDUMP_EXE";
+const COREDUMP_PID: &str = "COREDUMP_PID";
+const COREDUMP_UID: &str = "COREDUMP_UID";
+const COREDUMP_SIGNAL: &str = "COREDUMP_SIGNAL";
+const COREDUMP_SIGNAL_NAME: &str = "COREDUMP_SIGNAL_NAME";
+
+/// Line of the message of systemd-coredump starting the backtrace
+const STACK_TRACE_START: &str = "Stack trace of thread";
+
+/// Crash of a process, read from the entry logged by systemd-coredump
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Coredump {
+    pub cursor: String,
+    /// Time of the crash in microseconds since the epoch
+    pub realtime: u64,
+    pub boot_id: String,
+    pub pid: Option<u32>,
+    pub uid: Option<u32>,
+    pub exe: String,
+    /// Number of the signal that terminated the process, e.g. 11
+    pub signal: Option<u32>,
+    /// Name of the signal, e.g. SIGSEGV
+    pub signal_name: String,
+    pub unit: Option<String>,
+    pub user_unit: Option<String>,
+    /// Backtraces of the threads, if systemd-coredump could produce them
+    pub stack_trace: Option<String>,
+}
+
+impl Coredump {
+    /// Reads the crash from the fields of the entry
+    pub(crate) fn from_fields<F>(cursor: String, realtime: u64, mut get_field: F) -> Coredump
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let mut number = |field: &str| get_field(field).and_then(|v| v.parse().ok());
+
+        let pid = number(COREDUMP_PID);
+        let uid = number(COREDUMP_UID);
+        let signal = number(COREDUMP_SIGNAL);
+
+        Coredump {
+            cursor,
+            realtime,
+            boot_id: get_field(journal_fields::BOOT_ID).unwrap_or_default(),
+            pid,
+            uid,
+            exe: get_field(COREDUMP_EXE).unwrap_or_default(),
+            signal,
+            signal_name: get_field(COREDUMP_SIGNAL_NAME).unwrap_or_default(),
+            unit: get_field(journal_fields::COREDUMP_UNIT),
+            user_unit: get_field(journal_fields::COREDUMP_USER_UNIT),
+            stack_trace: get_field(journal_fields::MESSAGE).and_then(|m| stack_trace(&m)),
+        }
+    }
+
+    /// Query for the entries logged by the crashed unit, or by the process when it had none,
+    /// from `before_usec` microseconds before the crash up to the crash
+    pub fn related_logs_query(&self, before_usec: u64, fields: Vec<String>) -> Query {
+        let mut qb = QueryBuilder::default();
+        qb.with_fields(fields)
+            .with_limit(0)
+            .with_priority_above_or_equal_to(7)
+            .with_transports(vec![])
+            .with_boot_ids(vec![self.boot_id.clone()])
+            .with_date_more_than(self.realtime.saturating_sub(before_usec))
+            // The upper bound is exclusive, keep the entries logged at the time of the crash
+            .with_date_less_than(self.realtime + 1);
+
+        if let Some(unit) = &self.unit {
+            qb.with_units(vec![unit.clone()]);
+        } else if let Some(user_unit) = &self.user_unit {
+            qb.with_user_units(vec![user_unit.clone()], self.uid.unwrap_or_default());
+        } else if let Some(pid) = self.pid {
+            qb.with_pid(pid);
+        }
+
+        qb.build()
+    }
+}
+
+/// The backtraces at the end of the message, after the list of the loaded modules
+fn stack_trace(message: &str) -> Option<String> {
+    let start = message.find(STACK_TRACE_START)?;
+
+    Some(message[start..].trim_end().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Coredump;
+    use crate::journal::testing::{self, entry};
+    use crate::journal::{ImportedEntry, Journal, MemoryJournal};
+
+    const MINUTE: u64 = 60_000_000;
+
+    const CRASH_MESSAGE: &str = "Process 2411 (nginx) of user 33 dumped core.\n\n\
+        Module libc.so.6 from rpm glibc-2.39-1.x86_64\n\
+        Stack trace of thread 2411:\n\
+        #0  0x00007f2c1a0a3b1c __pthread_kill_implementation (libc.so.6 + 0x8fb1c)\n\
+        #1  0x000055d0c31e2f44 ngx_worker_process_cycle (nginx + 0x5ef44)\n";
+
+    fn logged(realtime: u64, boot: &str, fields: &[(&str, &str)]) -> ImportedEntry {
+        let mut all = vec![
+            ("PRIORITY", "6"),
+            ("_BOOT_ID", boot),
+            ("_TRANSPORT", "journal"),
+        ];
+        all.extend_from_slice(fields);
+
+        entry(realtime, &all)
+    }
+
+    fn crash(realtime: u64, boot: &str, unit: &str) -> ImportedEntry {
+        logged(
+            realtime,
+            boot,
+            &[
+                ("MESSAGE_ID", "fc2e22bc6ee647b6b90729ab34a250b1"),
+                ("MESSAGE", CRASH_MESSAGE),
+                ("_UID", "0"),
+                ("COREDUMP_UNIT", unit),
+                ("COREDUMP_PID", "2411"),
+                ("COREDUMP_UID", "33"),
+                ("COREDUMP_EXE", "/usr/sbin/nginx"),
+                ("COREDUMP_SIGNAL", "6"),
+                ("COREDUMP_SIGNAL_NAME", "SIGABRT"),
+            ],
+        )
+    }
+
+    fn journal() -> Journal<MemoryJournal> {
+        let start = 100 * MINUTE;
+        let nginx = |message| [("_SYSTEMD_UNIT", "nginx.service"), ("MESSAGE", message)];
+
+        testing::journal(vec![
+            crash(start - 10 * MINUTE, "b1", "nginx.service"),
+            logged(start, "b2", &nginx("started")),
+            logged(start + 7 * MINUTE, "b2", &nginx("worker busy")),
+            logged(
+                start + 8 * MINUTE,
+                "b2",
+                &[("_SYSTEMD_UNIT", "sshd.service"), ("MESSAGE", "login")],
+            ),
+            logged(start + 9 * MINUTE, "b2", &nginx("out of memory")),
+            crash(start + 10 * MINUTE, "b2", "nginx.service"),
+            logged(start + 11 * MINUTE, "b2", &nginx("restarted")),
+        ])
+    }
+
+    #[test]
+    fn lists_the_crashes_of_a_boot() {
+        let j = journal();
+
+        let all = j.list_coredumps(None).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].boot_id, "b2");
+
+        let coredumps = j.list_coredumps(Some("b2")).unwrap();
+        assert_eq!(coredumps.len(), 1);
+
+        let coredump = &coredumps[0];
+        assert_eq!(coredump.exe, "/usr/sbin/nginx");
+        assert_eq!(coredump.pid, Some(2411));
+        assert_eq!(coredump.uid, Some(33));
+        assert_eq!(coredump.signal, Some(6));
+        assert_eq!(coredump.signal_name, "SIGABRT");
+        assert_eq!(coredump.unit.as_deref(), Some("nginx.service"));
+        assert_eq!(coredump.user_unit, None);
+
+        let stack_trace = coredump.stack_trace.as_deref().unwrap();
+        assert!(stack_trace.starts_with("Stack trace of thread 2411:\n#0"));
+        assert!(stack_trace.ends_with("(nginx + 0x5ef44)"));
+
+        assert_eq!(j.get_coredump(&coredump.cursor).unwrap(), *coredump);
+    }
+
+    #[test]
+    fn logs_of_the_unit_before_the_crash() {
+        let j = journal();
+        let coredump = &j.list_coredumps(Some("b2")).unwrap()[0];
+
+        let q = coredump.related_logs_query(5 * MINUTE, vec!["MESSAGE".into()]);
+        let messages: Vec<String> = j
+            .query_logs(&q)
+            .unwrap()
+            .rows
+            .into_iter()
+            .map(|r| r[0].clone())
+            .collect();
+
+        // The crash itself is logged by systemd-coredump about the unit
+        assert_eq!(
+            messages,
+            vec![CRASH_MESSAGE, "out of memory", "worker busy"]
+        );
+    }
+
+    #[test]
+    fn without_stack_trace() {
+        let coredump = Coredump::from_fields("c1".into(), 1, |field| match field {
+            "MESSAGE" => Some("Process 1 (a) of user 0 dumped core.".into()),
+            "COREDUMP_PID" => Some("not a pid".into()),
+            _ => None,
+        });
+
+        assert_eq!(coredump.stack_trace, None);
+        assert_eq!(coredump.pid, None);
+        assert_eq!(coredump.exe, "");
+    }
+}
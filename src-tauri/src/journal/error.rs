@@ -0,0 +1,33 @@
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Error, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JournalError {
+    #[error("Internal error while invoking systemd. Error Code: {0}")]
+    Internal(i32),
+    #[error("Reached the end of the cursor")]
+    EndOfFile,
+    #[error("Could not find the requested entry")]
+    NotFound,
+    #[error("Invalid filter expression: {0}")]
+    InvalidFilter(String),
+    #[error("Invalid search: {0}")]
+    InvalidSearch(String),
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+    #[error("Invalid journal dump: {0}")]
+    InvalidImport(String),
+    #[error("Invalid journal file: {0}")]
+    InvalidFile(String),
+    #[error("I/O error: {0}")]
+    Io(String),
+    #[error("Session {0} is closed or expired")]
+    SessionNotFound(u64),
+}
+
+impl From<std::io::Error> for JournalError {
+    fn from(e: std::io::Error) -> Self {
+        JournalError::Io(e.to_string())
+    }
+}
@@ -0,0 +1,116 @@
+use super::error::JournalError;
+use super::journal_fields;
+use regex::{RegexSet, RegexSetBuilder};
+
+/// Entries to hide from the results. libsystemd has no negative matches,
+/// so these are checked on every entry after the matches, cheapest fields first.
+#[derive(Debug, Clone, Default)]
+pub struct Exclusions {
+    /// Compared with both `_SYSTEMD_UNIT` and `UNIT`, like the unit filter
+    units: Vec<String>,
+    /// Compared with `SYSLOG_IDENTIFIER`
+    identifiers: Vec<String>,
+    /// Regular expressions matching anywhere in `MESSAGE`
+    messages: Option<RegexSet>,
+}
+
+impl Exclusions {
+    pub fn new(
+        units: Vec<String>,
+        identifiers: Vec<String>,
+        message_patterns: &[String],
+    ) -> Result<Exclusions, JournalError> {
+        let messages = if message_patterns.is_empty() {
+            None
+        } else {
+            let set = RegexSetBuilder::new(message_patterns)
+                .size_limit(1 << 20)
+                .build()
+                .map_err(|e| JournalError::InvalidSearch(e.to_string()))?;
+            Some(set)
+        };
+
+        Ok(Exclusions {
+            units,
+            identifiers,
+            messages,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty() && self.identifiers.is_empty() && self.messages.is_none()
+    }
+
+    /// Whether the entry has to be left out, reading its fields through `get_field`
+    pub fn excludes<F>(&self, mut get_field: F) -> bool
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let mut has_value = |field: &str, values: &[String]| {
+            !values.is_empty() && get_field(field).is_some_and(|v| values.contains(&v))
+        };
+
+        if has_value(journal_fields::SYSTEMD_UNIT, &self.units)
+            || has_value(journal_fields::UNIT_FILTER, &self.units)
+            || has_value(journal_fields::SYSLOG_IDENTIFIER, &self.identifiers)
+        {
+            return true;
+        }
+
+        match &self.messages {
+            Some(set) => get_field(journal_fields::MESSAGE).is_some_and(|m| set.is_match(&m)),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Exclusions;
+    use std::collections::HashMap;
+
+    fn excludes(exclusions: &Exclusions, fields: &[(&str, &str)]) -> bool {
+        let fields: HashMap<&str, &str> = fields.iter().cloned().collect();
+        exclusions.excludes(|f| fields.get(f).map(|v| v.to_string()))
+    }
+
+    #[test]
+    fn units_identifiers_and_messages() {
+        let exclusions = Exclusions::new(
+            vec!["NetworkManager.service".into()],
+            vec!["kwin_x11".into()],
+            &["^Activating via".into(), "(?i)dhcp4".into()],
+        )
+        .unwrap();
+
+        assert!(excludes(
+            &exclusions,
+            &[("_SYSTEMD_UNIT", "NetworkManager.service")]
+        ));
+        assert!(excludes(&exclusions, &[("UNIT", "NetworkManager.service")]));
+        assert!(excludes(&exclusions, &[("SYSLOG_IDENTIFIER", "kwin_x11")]));
+        assert!(excludes(
+            &exclusions,
+            &[("MESSAGE", "Activating via systemd: service name")]
+        ));
+        assert!(excludes(&exclusions, &[("MESSAGE", "wlan0: DHCP4 lease")]));
+
+        assert!(!excludes(
+            &exclusions,
+            &[
+                ("_SYSTEMD_UNIT", "sshd.service"),
+                ("SYSLOG_IDENTIFIER", "sshd"),
+                ("MESSAGE", "Accepted publickey. Activating via ssh"),
+            ]
+        ));
+    }
+
+    #[test]
+    fn empty_and_invalid() {
+        let exclusions = Exclusions::new(vec![], vec![], &[]).unwrap();
+        assert!(exclusions.is_empty());
+        assert!(!excludes(&exclusions, &[("MESSAGE", "anything")]));
+
+        assert!(Exclusions::new(vec![], vec![], &["(".into()]).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::io::{self, Write};
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    /// One JSON object per line with the selected fields
+    JsonLines,
+    /// Comma separated values with the selected fields as columns
+    Csv,
+    /// Journal Export Format with every field, can be imported with systemd-journal-remote
+    JournalExport,
+}
+
+impl ExportFormat {
+    /// Whether the format includes every field of the entries instead of the selected ones
+    pub fn exports_all_fields(&self) -> bool {
+        *self == ExportFormat::JournalExport
+    }
+}
+
+/// Writes entries to the output in the given format
+pub struct EntryWriter<W: Write> {
+    out: W,
+    format: ExportFormat,
+    fields: Vec<String>,
+}
+
+impl<W: Write> EntryWriter<W> {
+    pub fn new(mut out: W, format: ExportFormat, fields: Vec<String>) -> io::Result<Self> {
+        if format == ExportFormat::Csv {
+            write_csv_line(&mut out, &fields)?;
+        }
+
+        Ok(EntryWriter {
+            out,
+            format,
+            fields,
+        })
+    }
+
+    pub fn format(&self) -> ExportFormat {
+        self.format
+    }
+
+    /// Fields written for every entry, unless the format exports all of them
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// Writes the values of the selected fields, in the same order
+    pub fn write_row(&mut self, row: &[String]) -> io::Result<()> {
+        match self.format {
+            ExportFormat::Csv => write_csv_line(&mut self.out, row),
+            ExportFormat::JsonLines => {
+                // Like journalctl, fields missing in the entry are left out
+                let object: Map<String, Value> = self
+                    .fields
+                    .iter()
+                    .zip(row)
+                    .filter(|(_, value)| !value.is_empty())
+                    .map(|(field, value)| (field.clone(), Value::String(value.clone())))
+                    .collect();
+
+                serde_json::to_writer(&mut self.out, &object)?;
+                self.out.write_all(b"\n")
+            }
+            ExportFormat::JournalExport => {
+                let fields: Vec<(String, Vec<u8>)> = self
+                    .fields
+                    .iter()
+                    .zip(row)
+                    .map(|(field, value)| (field.clone(), value.as_bytes().to_vec()))
+                    .collect();
+
+                self.write_entry(&fields)
+            }
+        }
+    }
+
+    /// Writes every field of an entry in the Journal Export Format
+    pub fn write_entry(&mut self, fields: &[(String, Vec<u8>)]) -> io::Result<()> {
+        for (name, value) in fields {
+            write_export_field(&mut self.out, name, value)?;
+        }
+
+        // Entries are separated by an empty line
+        self.out.write_all(b"\n")
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+fn write_csv_line<W: Write>(out: &mut W, values: &[String]) -> io::Result<()> {
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+
+        if value.contains([',', '"', '\n', '\r']) {
+            write!(out, "\"{}\"", value.replace('"', "\"\""))?;
+        } else {
+            out.write_all(value.as_bytes())?;
+        }
+    }
+
+    out.write_all(b"\r\n")
+}
+
+/// Text values are written as `FIELD=value`, anything else in the binary form:
+/// the field name, a newline, the size as little endian 64 bit integer and the raw value
+fn write_export_field<W: Write>(out: &mut W, name: &str, value: &[u8]) -> io::Result<()> {
+    let is_text = value.iter().all(|b| *b >= b' ' || *b == b'\t');
+
+    if is_text {
+        out.write_all(name.as_bytes())?;
+        out.write_all(b"=")?;
+        out.write_all(value)?;
+    } else {
+        out.write_all(name.as_bytes())?;
+        out.write_all(b"\n")?;
+        out.write_all(&(value.len() as u64).to_le_bytes())?;
+        out.write_all(value)?;
+    }
+
+    out.write_all(b"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EntryWriter, ExportFormat};
+
+    fn fields() -> Vec<String> {
+        vec!["MESSAGE".into(), "PRIORITY".into()]
+    }
+
+    #[test]
+    fn csv() {
+        let mut out = vec![];
+        let mut w = EntryWriter::new(&mut out, ExportFormat::Csv, fields()).unwrap();
+        w.write_row(&["plain".into(), "6".into()]).unwrap();
+        w.write_row(&["a, \"quoted\"\nvalue".into(), "3".into()])
+            .unwrap();
+        w.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "MESSAGE,PRIORITY\r\nplain,6\r\n\"a, \"\"quoted\"\"\nvalue\",3\r\n"
+        );
+    }
+
+    #[test]
+    fn json_lines() {
+        let mut out = vec![];
+        let mut w = EntryWriter::new(&mut out, ExportFormat::JsonLines, fields()).unwrap();
+        w.write_row(&["hello \"world\"".into(), "6".into()])
+            .unwrap();
+        w.write_row(&["no priority".into(), "".into()]).unwrap();
+        w.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"MESSAGE\":\"hello \\\"world\\\"\",\"PRIORITY\":\"6\"}\n{\"MESSAGE\":\"no priority\"}\n"
+        );
+    }
+
+    #[test]
+    fn journal_export_format() {
+        let mut out = vec![];
+        let mut w = EntryWriter::new(&mut out, ExportFormat::JournalExport, vec![]).unwrap();
+        w.write_entry(&[
+            ("__REALTIME_TIMESTAMP".into(), b"1700000000000000".to_vec()),
+            ("MESSAGE".into(), b"two\nlines".to_vec()),
+            ("PRIORITY".into(), b"6".to_vec()),
+        ])
+        .unwrap();
+        w.write_entry(&[("MESSAGE".into(), b"second".to_vec())])
+            .unwrap();
+        w.finish().unwrap();
+
+        let mut expected = b"__REALTIME_TIMESTAMP=1700000000000000\nMESSAGE\n".to_vec();
+        expected.extend(9u64.to_le_bytes());
+        expected.extend(b"two\nlines\nPRIORITY=6\n\nMESSAGE=second\n\n");
+
+        assert_eq!(out, expected);
+    }
+}
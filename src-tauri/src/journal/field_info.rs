@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+/// A field present in the journal that can be used as a column of a query
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FieldInfo {
+    pub name: String,
+    pub description: Option<String>,
+}
@@ -0,0 +1,466 @@
+use super::error::JournalError;
+use super::journal_fields;
+use nom::{
+    IResult,
+    branch::alt,
+    bytes::complete::{tag, tag_no_case, take_while, take_while1},
+    character::complete::{char, multispace0, multispace1},
+    combinator::{all_consuming, map, peek, value},
+    multi::many0,
+    sequence::{delimited, preceded, terminated, tuple},
+};
+use std::cmp::Ordering;
+
+/// Maximum number of conjunctions a single group can be expanded into
+/// before falling back to filtering the entries after reading them
+const MAX_DISJUNCTIONS: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+/// Boolean filter over the entry fields, e.g.
+/// `(_SYSTEMD_UNIT=nginx.service OR _COMM=php-fpm) AND PRIORITY<=3`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterExpression {
+    Compare {
+        field: String,
+        operator: Operator,
+        value: String,
+    },
+    Not(Box<FilterExpression>),
+    And(Vec<FilterExpression>),
+    Or(Vec<FilterExpression>),
+}
+
+/// A filter expression split into what libsystemd can match and what has to be checked per entry
+#[derive(Debug, Clone, Default)]
+pub struct CompiledFilter {
+    /// Groups combined with AND, each one is a disjunction of conjunctions of `FIELD=value` matches
+    pub match_groups: Vec<Vec<Vec<String>>>,
+    /// Expressions combined with AND that are evaluated on every entry
+    pub post_filters: Vec<FilterExpression>,
+}
+
+impl FilterExpression {
+    pub fn parse(input: &str) -> Result<FilterExpression, JournalError> {
+        match all_consuming(delimited(multispace0, or_expression, multispace0))(input) {
+            Ok((_, expression)) => Ok(expression),
+            Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+                let position = input.len() - e.input.len();
+                Err(JournalError::InvalidFilter(format!(
+                    "unexpected input at position {}: '{}'",
+                    position, e.input
+                )))
+            }
+            Err(nom::Err::Incomplete(_)) => Err(JournalError::InvalidFilter(
+                "unexpected end of the expression".into(),
+            )),
+        }
+    }
+
+    /// Evaluates the expression, `get_field` returns the value of a field of the entry if present
+    pub fn matches<F>(&self, get_field: &mut F) -> bool
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        match self {
+            FilterExpression::Compare {
+                field,
+                operator,
+                value,
+            } => match get_field(field) {
+                Some(actual) => {
+                    let ordering = compare_values(&actual, value);
+                    match operator {
+                        Operator::Equal => ordering == Ordering::Equal,
+                        Operator::NotEqual => ordering != Ordering::Equal,
+                        Operator::Less => ordering == Ordering::Less,
+                        Operator::LessOrEqual => ordering != Ordering::Greater,
+                        Operator::Greater => ordering == Ordering::Greater,
+                        Operator::GreaterOrEqual => ordering != Ordering::Less,
+                    }
+                }
+                None => *operator == Operator::NotEqual,
+            },
+            FilterExpression::Not(e) => !e.matches(get_field),
+            FilterExpression::And(expressions) => expressions.iter().all(|e| e.matches(get_field)),
+            FilterExpression::Or(expressions) => expressions.iter().any(|e| e.matches(get_field)),
+        }
+    }
+
+    pub fn compile(self) -> CompiledFilter {
+        let conjuncts = match self {
+            FilterExpression::And(expressions) => expressions,
+            e => vec![e],
+        };
+
+        let mut compiled = CompiledFilter::default();
+
+        for conjunct in conjuncts {
+            match conjunct.to_matches() {
+                Some(group) => compiled.match_groups.push(
+                    group
+                        .into_iter()
+                        .map(|c| c.into_iter().map(|(f, v)| format!("{}={}", f, v)).collect())
+                        .collect(),
+                ),
+                None => compiled.post_filters.push(conjunct),
+            }
+        }
+
+        compiled
+    }
+
+    /// Converts the expression into a disjunction of conjunctions of field matches,
+    /// if it can be expressed with libsystemd matches
+    fn to_matches(&self) -> Option<Vec<Vec<(String, String)>>> {
+        match self {
+            FilterExpression::Compare {
+                field,
+                operator,
+                value,
+            } => {
+                if *operator == Operator::Equal {
+                    return Some(vec![vec![(field.clone(), value.clone())]]);
+                }
+
+                // Priorities are a closed set, so any comparison can be listed, except
+                // inequality which also holds for the entries without a priority
+                if field == journal_fields::PRIORITY && *operator != Operator::NotEqual {
+                    let priorities: Vec<Vec<(String, String)>> = (0..=7)
+                        .map(|p: u32| p.to_string())
+                        .filter(|p| self.matches(&mut |_| Some(p.clone())))
+                        .map(|p| vec![(field.clone(), p)])
+                        .collect();
+
+                    if !priorities.is_empty() {
+                        return Some(priorities);
+                    }
+                }
+
+                None
+            }
+            FilterExpression::Not(_) => None,
+            FilterExpression::Or(expressions) => {
+                let mut disjunction = vec![];
+                for e in expressions {
+                    disjunction.extend(e.to_matches()?);
+                }
+
+                if disjunction.len() > MAX_DISJUNCTIONS {
+                    return None;
+                }
+
+                Some(disjunction)
+            }
+            FilterExpression::And(expressions) => {
+                let mut disjunction: Vec<Vec<(String, String)>> = vec![vec![]];
+
+                for e in expressions {
+                    let other = e.to_matches()?;
+                    if disjunction.len() * other.len() > MAX_DISJUNCTIONS {
+                        return None;
+                    }
+
+                    let mut product = Vec::with_capacity(disjunction.len() * other.len());
+                    for left in disjunction.iter() {
+                        for right in other.iter() {
+                            let mut conjunction = left.clone();
+                            for m in right {
+                                if !conjunction.contains(m) {
+                                    conjunction.push(m.clone());
+                                }
+                            }
+                            product.push(conjunction);
+                        }
+                    }
+                    disjunction = product;
+                }
+
+                // libsystemd ORs matches on the same field, an AND on them can't be expressed
+                let repeats_field = disjunction.iter().any(|conjunction| {
+                    conjunction
+                        .iter()
+                        .enumerate()
+                        .any(|(i, (field, _))| conjunction[..i].iter().any(|(f, _)| f == field))
+                });
+
+                if repeats_field {
+                    return None;
+                }
+
+                Some(disjunction)
+            }
+        }
+    }
+}
+
+/// Compares numerically when both sides are numbers, otherwise as text
+fn compare_values(actual: &str, expected: &str) -> Ordering {
+    match (actual.parse::<i64>(), expected.parse::<i64>()) {
+        (Ok(a), Ok(e)) => a.cmp(&e),
+        _ => actual.cmp(expected),
+    }
+}
+
+fn or_expression(i: &str) -> IResult<&str, FilterExpression> {
+    let (i, first) = and_expression(i)?;
+    let (i, rest) = many0(preceded(keyword("OR"), and_expression))(i)?;
+
+    Ok((i, combine(first, rest, FilterExpression::Or)))
+}
+
+fn and_expression(i: &str) -> IResult<&str, FilterExpression> {
+    let (i, first) = unary_expression(i)?;
+    let (i, rest) = many0(preceded(keyword("AND"), unary_expression))(i)?;
+
+    Ok((i, combine(first, rest, FilterExpression::And)))
+}
+
+fn unary_expression(i: &str) -> IResult<&str, FilterExpression> {
+    delimited(
+        multispace0,
+        alt((
+            map(preceded(keyword("NOT"), unary_expression), |e| {
+                FilterExpression::Not(Box::new(e))
+            }),
+            delimited(char('('), or_expression, preceded(multispace0, char(')'))),
+            comparison,
+        )),
+        multispace0,
+    )(i)
+}
+
+fn comparison(i: &str) -> IResult<&str, FilterExpression> {
+    let (i, (field, _, operator, _, value)) = tuple((
+        take_while1(|c: char| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'),
+        multispace0,
+        operator,
+        multispace0,
+        alt((quoted_value, bare_value)),
+    ))(i)?;
+
+    Ok((
+        i,
+        FilterExpression::Compare {
+            field: field.to_owned(),
+            operator,
+            value,
+        },
+    ))
+}
+
+fn operator(i: &str) -> IResult<&str, Operator> {
+    alt((
+        value(Operator::LessOrEqual, tag("<=")),
+        value(Operator::GreaterOrEqual, tag(">=")),
+        value(Operator::NotEqual, tag("!=")),
+        value(Operator::Equal, tag("=")),
+        value(Operator::Less, tag("<")),
+        value(Operator::Greater, tag(">")),
+    ))(i)
+}
+
+fn bare_value(i: &str) -> IResult<&str, String> {
+    map(
+        take_while1(|c: char| !c.is_whitespace() && c != '(' && c != ')' && c != '"'),
+        String::from,
+    )(i)
+}
+
+/// Double quoted value, supporting `\"` and `\\` escapes
+fn quoted_value(i: &str) -> IResult<&str, String> {
+    let (mut i, _) = char('"')(i)?;
+    let mut value = String::new();
+
+    loop {
+        let (rest, chunk) = take_while(|c: char| c != '"' && c != '\\')(i)?;
+        value.push_str(chunk);
+
+        let (rest, c) = alt((char('"'), char('\\')))(rest)?;
+        if c == '"' {
+            return Ok((rest, value));
+        }
+
+        let (rest, escaped) = alt((char('"'), char('\\')))(rest)?;
+        value.push(escaped);
+        i = rest;
+    }
+}
+
+/// Case insensitive keyword, it has to be followed by a space or a parenthesis
+fn keyword<'a>(word: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    delimited(
+        multispace0,
+        terminated(tag_no_case(word), peek(alt((multispace1, tag("("))))),
+        multispace0,
+    )
+}
+
+fn combine(
+    first: FilterExpression,
+    rest: Vec<FilterExpression>,
+    group: fn(Vec<FilterExpression>) -> FilterExpression,
+) -> FilterExpression {
+    if rest.is_empty() {
+        return first;
+    }
+
+    let mut expressions = Vec::with_capacity(rest.len() + 1);
+    expressions.push(first);
+    expressions.extend(rest);
+
+    group(expressions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FilterExpression, Operator};
+    use std::collections::HashMap;
+
+    fn compare(field: &str, operator: Operator, value: &str) -> FilterExpression {
+        FilterExpression::Compare {
+            field: field.into(),
+            operator,
+            value: value.into(),
+        }
+    }
+
+    #[test]
+    fn parse_precedence_and_groups() {
+        let e = FilterExpression::parse(
+            "(_SYSTEMD_UNIT=nginx.service OR _COMM=php-fpm) AND PRIORITY<=3",
+        )
+        .unwrap();
+
+        assert_eq!(
+            e,
+            FilterExpression::And(vec![
+                FilterExpression::Or(vec![
+                    compare("_SYSTEMD_UNIT", Operator::Equal, "nginx.service"),
+                    compare("_COMM", Operator::Equal, "php-fpm"),
+                ]),
+                compare("PRIORITY", Operator::LessOrEqual, "3"),
+            ])
+        );
+
+        let e = FilterExpression::parse("A=1 or B=2 and not C != \"x \\\"y\\\"\"").unwrap();
+        assert_eq!(
+            e,
+            FilterExpression::Or(vec![
+                compare("A", Operator::Equal, "1"),
+                FilterExpression::And(vec![
+                    compare("B", Operator::Equal, "2"),
+                    FilterExpression::Not(Box::new(compare("C", Operator::NotEqual, "x \"y\""))),
+                ]),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(FilterExpression::parse("").is_err());
+        assert!(FilterExpression::parse("_COMM=").is_err());
+        assert!(FilterExpression::parse("(_COMM=bash").is_err());
+        assert!(FilterExpression::parse("_COMM=bash AND").is_err());
+        assert!(FilterExpression::parse("_COMM bash").is_err());
+    }
+
+    #[test]
+    fn compile_to_matches() {
+        let compiled = FilterExpression::parse(
+            "(_SYSTEMD_UNIT=nginx.service OR _COMM=php-fpm) AND PRIORITY<=1",
+        )
+        .unwrap()
+        .compile();
+
+        assert!(compiled.post_filters.is_empty());
+        assert_eq!(
+            compiled.match_groups,
+            vec![
+                vec![
+                    vec!["_SYSTEMD_UNIT=nginx.service".to_owned()],
+                    vec!["_COMM=php-fpm".to_owned()]
+                ],
+                vec![vec!["PRIORITY=0".to_owned()], vec!["PRIORITY=1".to_owned()]],
+            ]
+        );
+    }
+
+    #[test]
+    fn compile_distributes_and_over_or() {
+        let compiled = FilterExpression::parse("_PID=1 AND (_UID=0 OR UNIT=a.service) OR _COMM=x")
+            .unwrap()
+            .compile();
+
+        assert!(compiled.post_filters.is_empty());
+        assert_eq!(
+            compiled.match_groups,
+            vec![vec![
+                vec!["_PID=1".to_owned(), "_UID=0".to_owned()],
+                vec!["_PID=1".to_owned(), "UNIT=a.service".to_owned()],
+                vec!["_COMM=x".to_owned()],
+            ]]
+        );
+    }
+
+    #[test]
+    fn compile_falls_back_to_post_filters() {
+        let compiled = FilterExpression::parse("_UID>=1000 AND _COMM=bash AND NOT _PID=1")
+            .unwrap()
+            .compile();
+
+        assert_eq!(
+            compiled.match_groups,
+            vec![vec![vec!["_COMM=bash".to_owned()]]]
+        );
+        assert_eq!(compiled.post_filters.len(), 2);
+
+        // Same field in a conjunction would be ORed by libsystemd
+        let compiled = FilterExpression::parse("(_COMM=a AND _COMM=b) OR _PID=1")
+            .unwrap()
+            .compile();
+        assert!(compiled.match_groups.is_empty());
+        assert_eq!(compiled.post_filters.len(), 1);
+
+        // Unless each one is in its own group
+        let compiled = FilterExpression::parse("_COMM=a AND _COMM=b")
+            .unwrap()
+            .compile();
+        assert_eq!(compiled.match_groups.len(), 2);
+
+        // Matches would drop the entries without a priority, which satisfy an inequality
+        let compiled = FilterExpression::parse("PRIORITY!=6").unwrap().compile();
+        assert!(compiled.match_groups.is_empty());
+        assert_eq!(compiled.post_filters.len(), 1);
+    }
+
+    #[test]
+    fn evaluate() {
+        let entry: HashMap<&str, &str> = HashMap::from([("_UID", "1000"), ("_COMM", "bash")]);
+        let mut get_field = |field: &str| entry.get(field).map(|v| v.to_string());
+
+        let e = FilterExpression::parse("_UID>=1000 AND _UID<65534 AND _COMM!=zsh").unwrap();
+        assert!(e.matches(&mut get_field));
+
+        let e = FilterExpression::parse("_UID>999 AND NOT (_COMM=bash OR _COMM=sh)").unwrap();
+        assert!(!e.matches(&mut get_field));
+
+        // Numbers are not compared as text
+        let e = FilterExpression::parse("_UID>900").unwrap();
+        assert!(e.matches(&mut get_field));
+
+        // Missing fields only satisfy inequality
+        let e = FilterExpression::parse("_EXE=/usr/bin/bash").unwrap();
+        assert!(!e.matches(&mut get_field));
+        let e = FilterExpression::parse("_EXE!=/usr/bin/bash").unwrap();
+        assert!(e.matches(&mut get_field));
+    }
+}
@@ -0,0 +1,60 @@
+use super::{Journal, JournalEntries, Query};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+
+/// Background task tailing a journal, equivalent to `journalctl -f`
+#[derive(Debug)]
+pub struct Follower {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Follower {
+    /// Starts following on a dedicated thread that owns the journal,
+    /// `on_entries` receives every batch of new entries matching the query
+    pub fn start<F>(journal: Journal, q: Query, on_entries: F) -> Follower
+    where
+        F: FnMut(JournalEntries) + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+
+        let handle = std::thread::spawn(move || {
+            if let Err(e) = journal.follow(&q, &thread_stop, on_entries) {
+                error!("Following the journal failed: {}", e);
+            }
+        });
+
+        Follower {
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    /// Asks the thread to stop, it notices within one wait for journal changes
+    pub fn request_stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    /// Stops the thread and blocks until it is done, to be called off the async runtime
+    pub fn stop(mut self) {
+        self.join();
+    }
+
+    fn join(&mut self) {
+        self.request_stop();
+
+        if let Some(handle) = self.handle.take() {
+            if handle.join().is_err() {
+                error!("Follow thread panicked");
+            }
+        }
+    }
+}
+
+impl Drop for Follower {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
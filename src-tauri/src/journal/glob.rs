@@ -0,0 +1,112 @@
+/// Whether the unit name is a shell-style pattern like `docker-*.scope`
+pub fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// Matches the whole text against a pattern with `*`, `?` and `[...]` classes,
+/// like fnmatch(3) without flags
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position after the last star and the text position it is retried from
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        let step = match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+                continue;
+            }
+            Some('?') => Some(p + 1),
+            Some('[') => match_class(&pattern, p, text[t]),
+            Some(c) => (*c == text[t]).then_some(p + 1),
+            None => None,
+        };
+
+        match (step, backtrack) {
+            (Some(next), _) => {
+                p = next;
+                t += 1;
+            }
+            (None, Some((star_p, star_t))) => {
+                p = star_p;
+                t = star_t + 1;
+                backtrack = Some((star_p, star_t + 1));
+            }
+            (None, None) => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Matches a character against the class starting at `start`,
+/// returning the position after the class if it matches
+fn match_class(pattern: &[char], start: usize, c: char) -> Option<usize> {
+    let mut i = start + 1;
+    let negated = matches!(pattern.get(i), Some('!') | Some('^'));
+    if negated {
+        i += 1;
+    }
+
+    let mut matched = false;
+    let mut first = true;
+    loop {
+        match pattern.get(i) {
+            // Unterminated, the bracket is an ordinary character
+            None => return (c == '[').then_some(start + 1),
+            Some(']') if !first => break,
+            Some(&low) => {
+                if pattern.get(i + 1) == Some(&'-') && pattern.get(i + 2).is_some_and(|h| *h != ']')
+                {
+                    matched |= low <= c && c <= pattern[i + 2];
+                    i += 3;
+                } else {
+                    matched |= low == c;
+                    i += 1;
+                }
+            }
+        }
+        first = false;
+    }
+
+    (matched != negated).then_some(i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{glob_match, is_glob};
+
+    #[test]
+    fn stars_and_question_marks() {
+        assert!(glob_match("docker-*.scope", "docker-3f2a.scope"));
+        assert!(glob_match("docker-*.scope", "docker-.scope"));
+        assert!(!glob_match("docker-*.scope", "docker-3f2a.service"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("user@*.service", "user@1000.service"));
+        assert!(glob_match("getty@tty?.service", "getty@tty1.service"));
+        assert!(!glob_match("getty@tty?.service", "getty@tty10.service"));
+        assert!(glob_match("*a*b", "xaxxab"));
+        assert!(!glob_match("*a*b", "xaxxa"));
+    }
+
+    #[test]
+    fn classes() {
+        assert!(glob_match("tty[0-9]", "tty7"));
+        assert!(!glob_match("tty[!0-9]", "tty7"));
+        assert!(glob_match("tty[!0-9]", "ttyS"));
+        assert!(glob_match("[]a]", "]"));
+        assert!(glob_match("a[", "a["));
+        assert!(!glob_match("a[", "ab"));
+    }
+
+    #[test]
+    fn detects_patterns() {
+        assert!(is_glob("docker-*.scope"));
+        assert!(is_glob("tty[12].service"));
+        assert!(!is_glob("sshd.service"));
+    }
+}
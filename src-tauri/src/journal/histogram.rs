@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+/// Number of entries per period of time, split by priority
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Histogram {
+    /// Start of the first bucket in microseconds since the epoch
+    pub from: u64,
+    /// End of the last bucket in microseconds since the epoch, exclusive
+    pub to: u64,
+    pub bucket_width: u64,
+    pub buckets: Vec<HistogramBucket>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramBucket {
+    /// Start of the bucket in microseconds since the epoch
+    pub start: u64,
+    pub total: u64,
+    /// Entries per priority, indexed from 0 ("emerg") to 7 ("debug")
+    pub priorities: [u64; 8],
+}
+
+impl Histogram {
+    /// Creates empty buckets covering the range, the start is aligned to the bucket width
+    /// so that consecutive requests produce the same buckets
+    pub fn new(from: u64, to: u64, bucket_width: u64) -> Histogram {
+        let from = from - from % bucket_width;
+        let count = to.saturating_sub(from).div_ceil(bucket_width);
+
+        let buckets = (0..count)
+            .map(|i| HistogramBucket {
+                start: from + i * bucket_width,
+                ..HistogramBucket::default()
+            })
+            .collect();
+
+        Histogram {
+            from,
+            to: from + count * bucket_width,
+            bucket_width,
+            buckets,
+        }
+    }
+
+    pub fn add(&mut self, timestamp: u64, priority: Option<usize>) {
+        if timestamp < self.from || timestamp >= self.to {
+            return;
+        }
+
+        let bucket = &mut self.buckets[((timestamp - self.from) / self.bucket_width) as usize];
+        bucket.total += 1;
+
+        if let Some(p) = priority.filter(|p| *p < bucket.priorities.len()) {
+            bucket.priorities[p] += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Histogram;
+
+    #[test]
+    fn buckets_are_aligned() {
+        let h = Histogram::new(1_050, 3_010, 1_000);
+        assert_eq!(h.from, 1_000);
+        assert_eq!(h.to, 4_000);
+        assert_eq!(h.buckets.len(), 3);
+        assert_eq!(h.buckets[2].start, 3_000);
+    }
+
+    #[test]
+    fn add_counts_per_priority() {
+        let mut h = Histogram::new(0, 2_000, 1_000);
+        h.add(10, Some(3));
+        h.add(999, Some(3));
+        h.add(1_000, Some(6));
+        h.add(1_500, None);
+        h.add(2_000, Some(0));
+
+        assert_eq!(h.buckets[0].total, 2);
+        assert_eq!(h.buckets[0].priorities[3], 2);
+        assert_eq!(h.buckets[1].total, 2);
+        assert_eq!(h.buckets[1].priorities[6], 1);
+        assert_eq!(h.buckets[1].priorities.iter().sum::<u64>(), 1);
+    }
+}
@@ -0,0 +1,351 @@
+use super::error::JournalError;
+use super::journal_fields;
+#[cfg(feature = "journal-file")]
+use super::journal_file::JournalFile;
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+
+/// Largest binary field accepted from a dump, so that a corrupt size can't exhaust the memory
+const MAX_BINARY_FIELD_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Entry read from a journal dump
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportedEntry {
+    /// Wallclock time of the entry in microseconds since the epoch
+    pub realtime: u64,
+    /// Time since the boot in microseconds, 0 if the dump doesn't have it
+    pub monotonic: u64,
+    pub cursor: String,
+    /// Fields in the order of the dump, a field can be repeated with several values
+    pub fields: Vec<(String, String)>,
+}
+
+impl ImportedEntry {
+    /// First value of the field
+    pub fn get(&self, field: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(name, _)| name == field)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether any of the values of the field is equal to `value`
+    pub fn has(&self, field: &str, value: &str) -> bool {
+        self.fields
+            .iter()
+            .any(|(name, v)| name == field && v == value)
+    }
+
+    fn push(&mut self, name: String, value: String) {
+        match name.as_str() {
+            journal_fields::REALTIME_TIMESTAMP => self.realtime = value.parse().unwrap_or(0),
+            journal_fields::MONOTONIC_TIMESTAMP => self.monotonic = value.parse().unwrap_or(0),
+            journal_fields::CURSOR => self.cursor = value,
+            // Other address fields, like __SEQNUM, are not kept
+            _ if name.starts_with("__") => {}
+            _ => self.fields.push((name, value)),
+        }
+    }
+
+    fn finish(mut self, index: usize) -> ImportedEntry {
+        if self.realtime == 0 {
+            self.realtime = self
+                .get(journal_fields::SOURCE_REALTIME_TIMESTAMP)
+                .and_then(|t| t.parse().ok())
+                .unwrap_or(0);
+        }
+
+        if self.cursor.is_empty() {
+            self.cursor = format!("i={}", index);
+        }
+
+        self
+    }
+}
+
+#[cfg(feature = "journal-file")]
+impl From<super::journal_file::Entry> for ImportedEntry {
+    fn from(entry: super::journal_file::Entry) -> Self {
+        ImportedEntry {
+            realtime: entry.realtime,
+            monotonic: entry.monotonic,
+            cursor: entry.cursor,
+            fields: entry
+                .fields
+                .into_iter()
+                .map(|(name, value)| (name, String::from_utf8_lossy(&value).into_owned()))
+                .collect(),
+        }
+    }
+}
+
+/// Reads the entries of a dump file, with the `journal-file` feature also of a .journal file
+pub fn read_file(path: &str) -> Result<Vec<ImportedEntry>, JournalError> {
+    #[cfg(feature = "journal-file")]
+    {
+        let data = std::fs::read(path)?;
+
+        if JournalFile::has_signature(&data) {
+            let entries = JournalFile::from_bytes(data)?.entries()?;
+            return Ok(entries.into_iter().map(ImportedEntry::from).collect());
+        }
+    }
+
+    parse(BufReader::new(File::open(path)?))
+}
+
+/// Reads a journal dump, either in the Journal Export Format (`journalctl -o export`)
+/// or as JSON objects (`journalctl -o json`, `json-pretty` or `json-seq`)
+pub fn parse<R: BufRead>(mut reader: R) -> Result<Vec<ImportedEntry>, JournalError> {
+    let is_json = loop {
+        let buf = reader.fill_buf()?;
+
+        match buf.iter().position(|b| !b.is_ascii_whitespace()) {
+            Some(i) => {
+                let first = buf[i];
+                reader.consume(i);
+                break first == b'{' || first == 0x1e;
+            }
+            None if buf.is_empty() => return Ok(vec![]),
+            None => {
+                let len = buf.len();
+                reader.consume(len);
+            }
+        }
+    };
+
+    if is_json {
+        parse_json(reader)
+    } else {
+        parse_export(reader)
+    }
+}
+
+/// Parses the Journal Export Format. Each entry is a list of fields terminated by an empty line,
+/// text fields are written as `FIELD=value` and binary ones as the field name, a newline,
+/// the size as little endian 64 bit integer, the raw value and a newline
+pub fn parse_export<R: BufRead>(mut reader: R) -> Result<Vec<ImportedEntry>, JournalError> {
+    let mut entries = vec![];
+    let mut entry = ImportedEntry::default();
+    let mut line = vec![];
+
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+
+        if line.is_empty() {
+            if !entry.fields.is_empty() {
+                entries.push(std::mem::take(&mut entry).finish(entries.len()));
+            }
+            continue;
+        }
+
+        match line.iter().position(|b| *b == b'=') {
+            Some(i) => entry.push(
+                String::from_utf8_lossy(&line[..i]).into_owned(),
+                String::from_utf8_lossy(&line[i + 1..]).into_owned(),
+            ),
+            None => {
+                let name = String::from_utf8_lossy(&line).into_owned();
+
+                let mut size = [0u8; 8];
+                reader.read_exact(&mut size).map_err(|_| truncated(&name))?;
+
+                let size = u64::from_le_bytes(size);
+                if size > MAX_BINARY_FIELD_SIZE {
+                    return Err(JournalError::InvalidImport(format!(
+                        "the binary field {} is larger than {} bytes",
+                        name, MAX_BINARY_FIELD_SIZE
+                    )));
+                }
+
+                // Grows with what is read, rather than trusting the size of the dump
+                let mut value = vec![];
+                if reader.by_ref().take(size).read_to_end(&mut value)? as u64 != size {
+                    return Err(truncated(&name));
+                }
+
+                let mut newline = [0u8; 1];
+                reader
+                    .read_exact(&mut newline)
+                    .map_err(|_| truncated(&name))?;
+                if newline[0] != b'\n' {
+                    return Err(JournalError::InvalidImport(format!(
+                        "missing newline after the binary field {}",
+                        name
+                    )));
+                }
+
+                entry.push(name, String::from_utf8_lossy(&value).into_owned());
+            }
+        }
+    }
+
+    if !entry.fields.is_empty() {
+        entries.push(entry.finish(entries.len()));
+    }
+
+    Ok(entries)
+}
+
+fn truncated(field: &str) -> JournalError {
+    JournalError::InvalidImport(format!("the binary field {} is truncated", field))
+}
+
+/// Parses a stream of JSON objects as written by journalctl. Values are strings,
+/// arrays of bytes for binary data, arrays of those for repeated fields,
+/// or null when the value was too large to be included
+pub fn parse_json<R: Read>(reader: R) -> Result<Vec<ImportedEntry>, JournalError> {
+    let mut entries = vec![];
+
+    // json-seq separates the records with the ASCII record separator
+    let reader = RecordSeparatorFilter(reader);
+
+    for value in serde_json::Deserializer::from_reader(reader).into_iter::<Value>() {
+        let value = value.map_err(|e| JournalError::InvalidImport(e.to_string()))?;
+
+        let Value::Object(object) = value else {
+            return Err(JournalError::InvalidImport(
+                "expected one JSON object per entry".into(),
+            ));
+        };
+
+        let mut entry = ImportedEntry::default();
+
+        for (name, value) in object {
+            match value {
+                Value::Array(values) if values.iter().all(|v| !v.is_number()) => {
+                    for v in values {
+                        if let Some(v) = json_value(v) {
+                            entry.push(name.clone(), v);
+                        }
+                    }
+                }
+                _ => {
+                    if let Some(v) = json_value(value) {
+                        entry.push(name, v);
+                    }
+                }
+            }
+        }
+
+        entries.push(entry.finish(entries.len()));
+    }
+
+    Ok(entries)
+}
+
+fn json_value(value: Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Array(bytes) => {
+            let bytes: Vec<u8> = bytes
+                .iter()
+                .filter_map(|b| b.as_u64())
+                .map(|b| b as u8)
+                .collect();
+            Some(String::from_utf8_lossy(&bytes).into_owned())
+        }
+        _ => None,
+    }
+}
+
+/// Replaces ASCII record separators with spaces
+struct RecordSeparatorFilter<R: Read>(R);
+
+impl<R: Read> Read for RecordSeparatorFilter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.0.read(buf)?;
+
+        for b in buf[..n].iter_mut() {
+            if *b == 0x1e {
+                *b = b' ';
+            }
+        }
+
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use crate::journal::JournalError;
+
+    #[test]
+    fn export_format() {
+        let mut dump = b"__CURSOR=s=1;i=1\n__REALTIME_TIMESTAMP=1700000000000000\n\
+            __MONOTONIC_TIMESTAMP=42\nPRIORITY=6\nMESSAGE\n"
+            .to_vec();
+        dump.extend(9u64.to_le_bytes());
+        dump.extend(b"two\nlines\n_SYSTEMD_UNIT=a.service\n\n");
+        dump.extend(b"__REALTIME_TIMESTAMP=1700000000000001\nMESSAGE=second\n");
+
+        let entries = parse(dump.as_slice()).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].cursor, "s=1;i=1");
+        assert_eq!(entries[0].realtime, 1_700_000_000_000_000);
+        assert_eq!(entries[0].get("MESSAGE"), Some("two\nlines"));
+        assert_eq!(entries[0].get("_SYSTEMD_UNIT"), Some("a.service"));
+        assert_eq!(entries[0].get("__MONOTONIC_TIMESTAMP"), None);
+        assert_eq!(entries[1].cursor, "i=1");
+        assert_eq!(entries[1].get("MESSAGE"), Some("second"));
+    }
+
+    #[test]
+    fn truncated_binary_field() {
+        let mut dump = b"MESSAGE\n".to_vec();
+        dump.extend(100u64.to_le_bytes());
+        dump.extend(b"short\n\n");
+
+        let e = parse(dump.as_slice()).unwrap_err();
+        assert!(matches!(e, JournalError::InvalidImport(_)));
+    }
+
+    #[test]
+    fn oversized_binary_field() {
+        let mut dump = b"MESSAGE\n".to_vec();
+        dump.extend(u64::MAX.to_le_bytes());
+        dump.extend(b"short\n\n");
+
+        let e = parse(dump.as_slice()).unwrap_err();
+        assert!(matches!(e, JournalError::InvalidImport(_)));
+    }
+
+    #[test]
+    fn json() {
+        let dump = r#"
+{"__CURSOR":"s=1;i=1","__REALTIME_TIMESTAMP":"1700000000000000","MESSAGE":"hello","PRIORITY":"6"}
+{"__REALTIME_TIMESTAMP":"1700000000000001","MESSAGE":[104,105],"TAG":["a","b"],"BIG":null}
+"#;
+
+        let entries = parse(dump.as_bytes()).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].cursor, "s=1;i=1");
+        assert_eq!(entries[0].get("MESSAGE"), Some("hello"));
+        assert_eq!(entries[1].get("MESSAGE"), Some("hi"));
+        assert!(entries[1].has("TAG", "a"));
+        assert!(entries[1].has("TAG", "b"));
+        assert_eq!(entries[1].get("BIG"), None);
+    }
+
+    #[test]
+    fn json_seq_and_pretty() {
+        let dump = "\x1e{\n\t\"MESSAGE\" : \"one\"\n}\n\x1e{\n\t\"MESSAGE\" : \"two\"\n}\n";
+
+        let entries = parse(dump.as_bytes()).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].get("MESSAGE"), Some("two"));
+    }
+}
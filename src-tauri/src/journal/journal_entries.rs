@@ -1,7 +1,7 @@
+use serde::{Deserialize, Serialize};
 use std::vec;
-use serde::{Serialize, Deserialize};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct JournalEntries {
     pub headers: Vec<String>,
     pub rows: Vec<Vec<String>>,
@@ -9,9 +9,9 @@ pub struct JournalEntries {
 
 impl JournalEntries {
     pub fn new(lenght: usize) -> JournalEntries {
-        JournalEntries{
+        JournalEntries {
             headers: vec![],
-            rows: Vec::with_capacity(lenght)
+            rows: Vec::with_capacity(lenght),
         }
     }
 }
@@ -20,13 +20,17 @@ impl JournalEntries {
 pub struct JournalEntry {
     pub headers: Vec<String>,
     pub values: Vec<String>,
+    /// Explanation of the message from the catalog, like `journalctl -x` shows
+    #[serde(default)]
+    pub catalog: Option<String>,
 }
 
 impl JournalEntry {
     pub fn new() -> JournalEntry {
-        JournalEntry{
+        JournalEntry {
             headers: vec![],
             values: vec![],
+            catalog: None,
         }
     }
 }
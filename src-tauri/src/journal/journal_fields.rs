@@ -6,6 +6,8 @@ pub const MESSAGE: &str = "MESSAGE";
 pub const PRIORITY: &str = "PRIORITY";
 /// low-level Unix error number causing this entry, if any
 pub const ERRNO: &str = "ERRNO";
+/// opaque position of the entry in the journal, not stored in the entry itself
+pub const CURSOR: &str = "__CURSOR";
 /// This is the time in microseconds since the epoch UTC, formatted as a decimal string
 pub const SOURCE_REALTIME_TIMESTAMP: &str = "_SOURCE_REALTIME_TIMESTAMP";
 
@@ -18,6 +20,8 @@ pub const GID: &str = "_GID";
 
 /// The name of the process
 pub const COMM: &str = "_COMM";
+/// syslog identifier, usually the program name
+pub const SYSLOG_IDENTIFIER: &str = "SYSLOG_IDENTIFIER";
 /// the executable path
 pub const EXE: &str = "_EXE";
 /// command line of the process
@@ -27,13 +31,33 @@ pub const CMDLINE: &str = "_CMDLINE";
 pub const SYSTEMD_SLICE: &str = "_SYSTEMD_SLICE";
 /// the systemd unit name
 pub const SYSTEMD_UNIT: &str = "_SYSTEMD_UNIT";
+/// the unit name in the service manager of the user
+pub const SYSTEMD_USER_UNIT: &str = "_SYSTEMD_USER_UNIT";
+/// the slice unit name in the service manager of the user
+pub const SYSTEMD_USER_SLICE: &str = "_SYSTEMD_USER_SLICE";
 /// The control group path in the systemd hierarchy
 pub const SYSTEMD_CGROUP: &str = "_SYSTEMD_CGROUP";
 
 /// unit is used for filtering
 pub const UNIT_FILTER: &str = "UNIT";
+/// user unit a message of the user's service manager is about
+pub const USER_UNIT: &str = "USER_UNIT";
+/// unit of the program a message logged by a privileged daemon is about
+pub const OBJECT_SYSTEMD_UNIT: &str = "OBJECT_SYSTEMD_UNIT";
+/// unit of the process that crashed, logged by systemd-coredump
+pub const COREDUMP_UNIT: &str = "COREDUMP_UNIT";
+/// user unit of the process that crashed, logged by systemd-coredump
+pub const COREDUMP_USER_UNIT: &str = "COREDUMP_USER_UNIT";
+/// user unit of the program a message logged by a privileged daemon is about
+pub const OBJECT_SYSTEMD_USER_UNIT: &str = "OBJECT_SYSTEMD_USER_UNIT";
+/// MESSAGE_ID of the entries logged by systemd-coredump
+pub const COREDUMP_MESSAGE_ID: &str = "fc2e22bc6ee647b6b90729ab34a250b1";
 /// The kernel boot ID
 pub const BOOT_ID: &str = "_BOOT_ID";
+/// The name of the originating host
+pub const HOSTNAME: &str = "_HOSTNAME";
+/// The journal namespace the entry was stored in, missing in the default namespace
+pub const NAMESPACE: &str = "_NAMESPACE";
 
 /// How the entry was received by the journal service
 /// Valid transports are:
@@ -44,3 +68,108 @@ pub const BOOT_ID: &str = "_BOOT_ID";
 ///   stdout: for those read from a service's standard output or error output
 ///   kernel: for those read from the kernel
 pub const TRANSPORT: &str = "_TRANSPORT";
+
+/// serial number of the audit event the entry is a record of
+pub const AUDIT_ID: &str = "_AUDIT_ID";
+/// name of the type of the audit record, e.g. AVC or SYSCALL
+pub const AUDIT_TYPE_NAME: &str = "_AUDIT_TYPE_NAME";
+
+/// The kernel subsystem name
+pub const KERNEL_SUBSYSTEM: &str = "_KERNEL_SUBSYSTEM";
+/// The kernel device name, e.g. b8:0 or +usb:1-1.4
+pub const KERNEL_DEVICE: &str = "_KERNEL_DEVICE";
+/// The kernel device name as it shows up in the device tree below /sys
+pub const UDEV_SYSNAME: &str = "_UDEV_SYSNAME";
+/// The device node path of this device in /dev
+pub const UDEV_DEVNODE: &str = "_UDEV_DEVNODE";
+
+/// Pseudo field with the wallclock time of the entry in microseconds, read from the entry metadata
+pub const REALTIME: &str = "__REALTIME";
+/// Wallclock time of the entry as named in the Journal Export Format
+pub const REALTIME_TIMESTAMP: &str = "__REALTIME_TIMESTAMP";
+/// Pseudo field with the time since the boot in microseconds, read from the entry metadata
+pub const MONOTONIC: &str = "__MONOTONIC";
+/// Time since the boot as named in the Journal Export Format
+pub const MONOTONIC_TIMESTAMP: &str = "__MONOTONIC_TIMESTAMP";
+
+/// Description of the fields defined by systemd, see systemd.journal-fields(7)
+pub fn describe(field: &str) -> Option<&'static str> {
+    let description = match field {
+        // User journal fields
+        MESSAGE => "Human-readable message string for this entry",
+        MESSAGE_ID => "128-bit message identifier for recognizing certain message types",
+        PRIORITY => "Priority value between 0 (\"emerg\") and 7 (\"debug\")",
+        "CODE_FILE" => "Source code file generating this message",
+        "CODE_LINE" => "Source code line number generating this message",
+        "CODE_FUNC" => "Source code function generating this message",
+        ERRNO => "Low-level Unix error number causing this entry, if any",
+        "INVOCATION_ID" => "Invocation ID of the unit the message is about",
+        "USER_INVOCATION_ID" => "Invocation ID of the user unit the message is about",
+        "SYSLOG_FACILITY" => "Syslog facility",
+        "SYSLOG_IDENTIFIER" => "Syslog identifier, usually the program name",
+        "SYSLOG_PID" => "Client PID as reported in the syslog message",
+        "SYSLOG_TIMESTAMP" => "Timestamp as reported in the original syslog message",
+        "SYSLOG_RAW" => "Original syslog line as received, if it was not parsed losslessly",
+        "DOCUMENTATION" => "Documentation URL with further information about the message",
+        "TID" => "Numeric thread ID of the thread logging the message",
+        UNIT_FILTER => "Unit the message is about, logged by systemd itself",
+        USER_UNIT => "User unit the message is about, logged by the user systemd instance",
+        // Trusted journal fields
+        PID => "Process ID of the process the journal entry originates from",
+        UID => "User ID of the process the journal entry originates from",
+        GID => "Group ID of the process the journal entry originates from",
+        COMM => "Name of the process the journal entry originates from",
+        EXE => "Executable path of the process the journal entry originates from",
+        CMDLINE => "Command line of the process the journal entry originates from",
+        "_CAP_EFFECTIVE" => "Effective capabilities of the process",
+        "_AUDIT_SESSION" => "Session of the process, as maintained by the kernel audit subsystem",
+        "_AUDIT_LOGINUID" => "Login UID of the process, as maintained by the kernel audit",
+        SYSTEMD_CGROUP => "Control group path in the systemd hierarchy",
+        SYSTEMD_SLICE => "Systemd slice unit name",
+        SYSTEMD_UNIT => "Systemd unit name",
+        SYSTEMD_USER_UNIT => "Systemd user session unit name",
+        SYSTEMD_USER_SLICE => "Systemd user session slice unit name",
+        "_SYSTEMD_SESSION" => "Systemd session ID",
+        "_SYSTEMD_OWNER_UID" => "Owner UID of the systemd user unit or session",
+        "_SYSTEMD_INVOCATION_ID" => "Invocation ID of the unit runtime cycle the entry belongs to",
+        "_SELINUX_CONTEXT" => "SELinux security context of the originating process",
+        SOURCE_REALTIME_TIMESTAMP => "Earliest trusted timestamp of the message, in microseconds",
+        BOOT_ID => "Kernel boot ID for the boot the message was generated in",
+        "_MACHINE_ID" => "Machine ID of the originating host",
+        HOSTNAME => "Name of the originating host",
+        TRANSPORT => "How the entry was received by the journal service",
+        "_STREAM_ID" => "Stream ID for entries received from a service's standard output",
+        "_LINE_BREAK" => "Why the line was broken for entries received from standard output",
+        NAMESPACE => "Journal namespace the entry was stored in",
+        "_RUNTIME_SCOPE" => "Whether the entry comes from the initrd or the system",
+        // Kernel journal fields
+        KERNEL_DEVICE => "Kernel device name",
+        KERNEL_SUBSYSTEM => "Kernel subsystem name",
+        UDEV_SYSNAME => "Kernel device name as it shows up in the device tree below /sys",
+        UDEV_DEVNODE => "Device node path of this device in /dev",
+        "_UDEV_DEVLINK" => "Additional symlink names pointing to the device node in /dev",
+        // Fields to log on behalf of a different program
+        COREDUMP_UNIT => "Unit of the process that crashed, logged by systemd-coredump",
+        COREDUMP_USER_UNIT => "User unit of the process that crashed, logged by systemd-coredump",
+        "OBJECT_PID" => "PID of the program the message is about",
+        "OBJECT_UID" => "User ID of the program the message is about",
+        "OBJECT_GID" => "Group ID of the program the message is about",
+        "OBJECT_COMM" => "Name of the program the message is about",
+        "OBJECT_EXE" => "Executable path of the program the message is about",
+        "OBJECT_CMDLINE" => "Command line of the program the message is about",
+        "OBJECT_AUDIT_SESSION" => "Audit session of the program the message is about",
+        "OBJECT_AUDIT_LOGINUID" => "Audit login UID of the program the message is about",
+        "OBJECT_SYSTEMD_CGROUP" => "Control group of the program the message is about",
+        "OBJECT_SYSTEMD_SESSION" => "Systemd session of the program the message is about",
+        "OBJECT_SYSTEMD_OWNER_UID" => "Owner UID of the program the message is about",
+        OBJECT_SYSTEMD_UNIT => "Systemd unit of the program the message is about",
+        OBJECT_SYSTEMD_USER_UNIT => "Systemd user unit of the program the message is about",
+        // Address fields
+        CURSOR => "Position of the entry in the journal",
+        REALTIME => "Wallclock time of the entry, in microseconds",
+        MONOTONIC => "Time since the boot the entry was logged in, in microseconds",
+        _ => return None,
+    };
+
+    Some(description)
+}
@@ -0,0 +1,108 @@
+use std::hash::Hasher;
+
+/// Jenkins `hashlittle2` from lookup3.c, the hash of files without the keyed hash flag.
+/// The primary and secondary hashes are combined into 64 bits like systemd does.
+pub fn jenkins_hash64(data: &[u8]) -> u64 {
+    let mut a: u32 = 0xdeadbeef_u32.wrapping_add(data.len() as u32);
+    let mut b = a;
+    let mut c = a;
+
+    let mut k = data;
+    while k.len() > 12 {
+        a = a.wrapping_add(le32(&k[0..4]));
+        b = b.wrapping_add(le32(&k[4..8]));
+        c = c.wrapping_add(le32(&k[8..12]));
+        mix(&mut a, &mut b, &mut c);
+        k = &k[12..];
+    }
+
+    if k.is_empty() {
+        return ((c as u64) << 32) | b as u64;
+    }
+
+    // The last block is zero padded
+    let mut tail = [0u8; 12];
+    tail[..k.len()].copy_from_slice(k);
+    a = a.wrapping_add(le32(&tail[0..4]));
+    b = b.wrapping_add(le32(&tail[4..8]));
+    c = c.wrapping_add(le32(&tail[8..12]));
+    finalize(&mut a, &mut b, &mut c);
+
+    ((c as u64) << 32) | b as u64
+}
+
+/// SipHash-2-4 keyed with the file id, used when the file has the keyed hash flag
+#[allow(deprecated)]
+pub fn siphash24(data: &[u8], key: &[u8; 16]) -> u64 {
+    let k0 = u64::from_le_bytes(key[0..8].try_into().unwrap());
+    let k1 = u64::from_le_bytes(key[8..16].try_into().unwrap());
+
+    let mut hasher = std::hash::SipHasher::new_with_keys(k0, k1);
+    hasher.write(data);
+    hasher.finish()
+}
+
+fn le32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(bytes.try_into().unwrap())
+}
+
+fn mix(a: &mut u32, b: &mut u32, c: &mut u32) {
+    *a = a.wrapping_sub(*c);
+    *a ^= c.rotate_left(4);
+    *c = c.wrapping_add(*b);
+    *b = b.wrapping_sub(*a);
+    *b ^= a.rotate_left(6);
+    *a = a.wrapping_add(*c);
+    *c = c.wrapping_sub(*b);
+    *c ^= b.rotate_left(8);
+    *b = b.wrapping_add(*a);
+    *a = a.wrapping_sub(*c);
+    *a ^= c.rotate_left(16);
+    *c = c.wrapping_add(*b);
+    *b = b.wrapping_sub(*a);
+    *b ^= a.rotate_left(19);
+    *a = a.wrapping_add(*c);
+    *c = c.wrapping_sub(*b);
+    *c ^= b.rotate_left(4);
+    *b = b.wrapping_add(*a);
+}
+
+fn finalize(a: &mut u32, b: &mut u32, c: &mut u32) {
+    *c ^= *b;
+    *c = c.wrapping_sub(b.rotate_left(14));
+    *a ^= *c;
+    *a = a.wrapping_sub(c.rotate_left(11));
+    *b ^= *a;
+    *b = b.wrapping_sub(a.rotate_left(25));
+    *c ^= *b;
+    *c = c.wrapping_sub(b.rotate_left(16));
+    *a ^= *c;
+    *a = a.wrapping_sub(c.rotate_left(4));
+    *b ^= *a;
+    *b = b.wrapping_sub(a.rotate_left(14));
+    *c ^= *b;
+    *c = c.wrapping_sub(b.rotate_left(24));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{jenkins_hash64, siphash24};
+
+    #[test]
+    fn jenkins() {
+        // Reference values from the lookup3.c test driver
+        assert_eq!(jenkins_hash64(b""), 0xdeadbeef_deadbeef);
+        assert_eq!(
+            jenkins_hash64(b"Four score and seven years ago"),
+            0x17770551_ce7226e6
+        );
+    }
+
+    #[test]
+    fn siphash() {
+        // Reference vector from the SipHash paper, 15 bytes with the key 00..0f
+        let key: [u8; 16] = core::array::from_fn(|i| i as u8);
+        let data: Vec<u8> = (0..15).collect();
+        assert_eq!(siphash24(&data, &key), 0xa129ca6149be45e5);
+    }
+}
@@ -0,0 +1,157 @@
+use super::{le64, read_id};
+use crate::journal::JournalError;
+use bitflags::bitflags;
+
+pub const SIGNATURE: &[u8; 8] = b"LPKSHHRH";
+
+/// Size of the header in the first version of the format, later versions append fields
+const MIN_HEADER_SIZE: u64 = 208;
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct CompatibleFlags: u32 {
+        const SEALED = 1 << 0;
+        const TAIL_ENTRY_BOOT_ID = 1 << 1;
+        const SEALED_CONTINUOUS = 1 << 2;
+    }
+}
+
+bitflags! {
+    /// Features that have to be understood to read the file
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct IncompatibleFlags: u32 {
+        const COMPRESSED_XZ = 1 << 0;
+        const COMPRESSED_LZ4 = 1 << 1;
+        const KEYED_HASH = 1 << 2;
+        const COMPRESSED_ZSTD = 1 << 3;
+        const COMPACT = 1 << 4;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileState {
+    /// Closed cleanly
+    Offline,
+    /// Being written, or the writer did not close it
+    Online,
+    /// Rotated, it won't be written again
+    Archived,
+}
+
+/// Header at the start of every journal file, integers are little endian
+#[derive(Debug, Clone)]
+pub struct Header {
+    pub compatible_flags: CompatibleFlags,
+    pub incompatible_flags: IncompatibleFlags,
+    pub state: FileState,
+    pub file_id: [u8; 16],
+    pub machine_id: [u8; 16],
+    pub tail_entry_boot_id: [u8; 16],
+    pub seqnum_id: [u8; 16],
+    pub header_size: u64,
+    pub arena_size: u64,
+    pub data_hash_table_offset: u64,
+    pub data_hash_table_size: u64,
+    pub field_hash_table_offset: u64,
+    pub field_hash_table_size: u64,
+    pub tail_object_offset: u64,
+    pub n_objects: u64,
+    pub n_entries: u64,
+    pub tail_entry_seqnum: u64,
+    pub head_entry_seqnum: u64,
+    pub entry_array_offset: u64,
+    pub head_entry_realtime: u64,
+    pub tail_entry_realtime: u64,
+    pub tail_entry_monotonic: u64,
+    /// Zero when the header is older than systemd 187
+    pub n_data: u64,
+    /// Zero when the header is older than systemd 187
+    pub n_fields: u64,
+}
+
+impl Header {
+    pub fn parse(data: &[u8]) -> Result<Header, JournalError> {
+        if data.len() < MIN_HEADER_SIZE as usize || &data[0..8] != SIGNATURE {
+            return Err(JournalError::InvalidFile("not a journal file".into()));
+        }
+
+        let compatible_flags = CompatibleFlags::from_bits_retain(le32(data, 8));
+        let incompatible_flags = IncompatibleFlags::from_bits(le32(data, 12)).ok_or_else(|| {
+            JournalError::InvalidFile(format!(
+                "unsupported incompatible flags {:#x}",
+                le32(data, 12)
+            ))
+        })?;
+
+        let state = match data[16] {
+            0 => FileState::Offline,
+            1 => FileState::Online,
+            2 => FileState::Archived,
+            s => return Err(JournalError::InvalidFile(format!("unknown state {}", s))),
+        };
+
+        let header_size = le64(data, 88)?;
+        if header_size < MIN_HEADER_SIZE || header_size > data.len() as u64 {
+            return Err(JournalError::InvalidFile(format!(
+                "invalid header size {}",
+                header_size
+            )));
+        }
+
+        // Fields appended in later versions are only there if the header is large enough
+        let optional = |offset: u64| {
+            if offset + 8 <= header_size {
+                le64(data, offset)
+            } else {
+                Ok(0)
+            }
+        };
+
+        Ok(Header {
+            compatible_flags,
+            incompatible_flags,
+            state,
+            file_id: read_id(data, 24)?,
+            machine_id: read_id(data, 40)?,
+            tail_entry_boot_id: read_id(data, 56)?,
+            seqnum_id: read_id(data, 72)?,
+            header_size,
+            arena_size: le64(data, 96)?,
+            data_hash_table_offset: le64(data, 104)?,
+            data_hash_table_size: le64(data, 112)?,
+            field_hash_table_offset: le64(data, 120)?,
+            field_hash_table_size: le64(data, 128)?,
+            tail_object_offset: le64(data, 136)?,
+            n_objects: le64(data, 144)?,
+            n_entries: le64(data, 152)?,
+            tail_entry_seqnum: le64(data, 160)?,
+            head_entry_seqnum: le64(data, 168)?,
+            entry_array_offset: le64(data, 176)?,
+            head_entry_realtime: le64(data, 184)?,
+            tail_entry_realtime: le64(data, 192)?,
+            tail_entry_monotonic: le64(data, 200)?,
+            n_data: optional(208)?,
+            n_fields: optional(216)?,
+        })
+    }
+
+    pub fn is_compact(&self) -> bool {
+        self.incompatible_flags.contains(IncompatibleFlags::COMPACT)
+    }
+
+    /// Algorithm the data objects of the file may be compressed with
+    pub fn compression(&self) -> Option<&'static str> {
+        [
+            (IncompatibleFlags::COMPRESSED_ZSTD, "zstd"),
+            (IncompatibleFlags::COMPRESSED_LZ4, "lz4"),
+            (IncompatibleFlags::COMPRESSED_XZ, "xz"),
+        ]
+        .into_iter()
+        .find(|(flag, _)| self.incompatible_flags.contains(*flag))
+        .map(|(_, name)| name)
+    }
+}
+
+fn le32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
@@ -0,0 +1,43 @@
+//! Read-only parser of the journal file format, without libsystemd.
+//! See https://systemd.io/JOURNAL_FILE_FORMAT/
+//!
+//! The header is always available, reading the entries needs the `journal-file` feature.
+//! Imported .journal files are read with it, and every source when built without the
+//! `libsystemd` feature.
+
+#[cfg(feature = "journal-file")]
+mod hash;
+mod header;
+#[cfg(feature = "journal-file")]
+mod reader;
+
+use super::error::JournalError;
+pub use header::{FileState, Header};
+#[cfg(feature = "journal-file")]
+pub use reader::{Entry, JournalFile};
+
+fn slice(data: &[u8], start: u64, end: u64) -> Result<&[u8], JournalError> {
+    if start > end || end > data.len() as u64 {
+        return Err(JournalError::InvalidFile(format!(
+            "range {}..{} is out of bounds",
+            start, end
+        )));
+    }
+
+    Ok(&data[start as usize..end as usize])
+}
+
+fn le64(data: &[u8], offset: u64) -> Result<u64, JournalError> {
+    let bytes = slice(data, offset, offset.saturating_add(8))?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_id(data: &[u8], offset: u64) -> Result<[u8; 16], JournalError> {
+    let bytes = slice(data, offset, offset.saturating_add(16))?;
+    Ok(bytes.try_into().unwrap())
+}
+
+/// Lowercase hex of an id, the way libsystemd formats them
+pub fn hex(id: &[u8]) -> String {
+    id.iter().map(|b| format!("{:02x}", b)).collect()
+}
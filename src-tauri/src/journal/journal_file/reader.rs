@@ -0,0 +1,794 @@
+use super::header::{self, Header, IncompatibleFlags};
+use super::{hash, hex, le64, read_id, slice};
+use crate::journal::JournalError;
+use bitflags::bitflags;
+use std::borrow::Cow;
+use std::io::{Read, Write};
+
+/// Largest payload accepted after decompression, protects against corrupted sizes
+const MAX_DECOMPRESSED_SIZE: usize = 64 * 1024 * 1024;
+
+const OBJECT_HEADER_SIZE: u64 = 16;
+
+const OBJECT_DATA: u8 = 1;
+const OBJECT_FIELD: u8 = 2;
+const OBJECT_ENTRY: u8 = 3;
+const OBJECT_ENTRY_ARRAY: u8 = 6;
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct ObjectFlags: u8 {
+        const COMPRESSED_XZ = 1 << 0;
+        const COMPRESSED_LZ4 = 1 << 1;
+        const COMPRESSED_ZSTD = 1 << 2;
+    }
+}
+
+/// Entry read from a journal file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub seqnum: u64,
+    /// Wallclock time in microseconds since the epoch
+    pub realtime: u64,
+    /// Time in microseconds since the boot
+    pub monotonic: u64,
+    pub boot_id: String,
+    /// Same format as the cursors of libsystemd
+    pub cursor: String,
+    pub fields: Vec<(String, Vec<u8>)>,
+}
+
+/// A whole journal file loaded in memory
+pub struct JournalFile {
+    data: Vec<u8>,
+    header: Header,
+}
+
+impl JournalFile {
+    pub fn open(path: &str) -> Result<JournalFile, JournalError> {
+        JournalFile::from_bytes(std::fs::read(path)?)
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> Result<JournalFile, JournalError> {
+        let header = Header::parse(&data)?;
+
+        Ok(JournalFile { data, header })
+    }
+
+    /// Whether the bytes start with the signature of journal files
+    pub fn has_signature(data: &[u8]) -> bool {
+        data.starts_with(header::SIGNATURE)
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Every entry of the file, in the order they were written
+    pub fn entries(&self) -> Result<Vec<Entry>, JournalError> {
+        self.entry_offsets()?
+            .into_iter()
+            .map(|offset| self.entry(offset))
+            .collect()
+    }
+
+    fn entry_offsets(&self) -> Result<Vec<u64>, JournalError> {
+        self.entry_array_items(self.header.entry_array_offset, self.header.n_entries)
+    }
+
+    /// Entries having a `FIELD=value` pair, found through the data hash table
+    pub fn entries_with(&self, field: &str, value: &[u8]) -> Result<Vec<Entry>, JournalError> {
+        let Some(offset) = self.find_data(field, value)? else {
+            return Ok(vec![]);
+        };
+
+        let object = self.object(offset, OBJECT_DATA)?;
+        let first = le64(object, 40)?;
+        let array = le64(object, 48)?;
+        let n_entries = le64(object, 56)?;
+
+        if n_entries == 0 {
+            return Ok(vec![]);
+        }
+
+        let mut offsets = vec![first];
+        offsets.extend(self.entry_array_items(array, n_entries - 1)?);
+
+        offsets.into_iter().map(|o| self.entry(o)).collect()
+    }
+
+    /// Names of the fields in the file, from the field hash table
+    pub fn fields(&self) -> Result<Vec<String>, JournalError> {
+        let mut fields = vec![];
+
+        for head in self.hash_table_heads(
+            self.header.field_hash_table_offset,
+            self.header.field_hash_table_size,
+        )? {
+            let mut offset = head;
+
+            while offset != 0 {
+                let object = self.object(offset, OBJECT_FIELD)?;
+                fields.push(String::from_utf8_lossy(tail(object, 40)?).into_owned());
+                offset = next_offset(offset, le64(object, 24)?)?;
+            }
+        }
+
+        fields.sort();
+
+        Ok(fields)
+    }
+
+    fn find_data(&self, field: &str, value: &[u8]) -> Result<Option<u64>, JournalError> {
+        let mut payload = Vec::with_capacity(field.len() + 1 + value.len());
+        payload.extend(field.as_bytes());
+        payload.push(b'=');
+        payload.extend(value);
+
+        let hash = self.hash(&payload);
+        let buckets = self.header.data_hash_table_size / 16;
+
+        if buckets == 0 {
+            return Ok(None);
+        }
+
+        let bucket = self.header.data_hash_table_offset + (hash % buckets) * 16;
+        let mut offset = le64(&self.data, bucket)?;
+
+        while offset != 0 {
+            let object = self.object(offset, OBJECT_DATA)?;
+
+            if le64(object, 16)? == hash && *self.data_payload(offset)? == *payload {
+                return Ok(Some(offset));
+            }
+
+            offset = next_offset(offset, le64(object, 24)?)?;
+        }
+
+        Ok(None)
+    }
+
+    fn hash(&self, payload: &[u8]) -> u64 {
+        if self
+            .header
+            .incompatible_flags
+            .contains(IncompatibleFlags::KEYED_HASH)
+        {
+            hash::siphash24(payload, &self.header.file_id)
+        } else {
+            hash::jenkins_hash64(payload)
+        }
+    }
+
+    fn hash_table_heads(&self, offset: u64, size: u64) -> Result<Vec<u64>, JournalError> {
+        let items = slice(&self.data, offset, offset.saturating_add(size))?;
+
+        items
+            .chunks_exact(16)
+            .map(|item| le64(item, 0))
+            .filter(|head| !matches!(head, Ok(0)))
+            .collect()
+    }
+
+    fn entry(&self, offset: u64) -> Result<Entry, JournalError> {
+        let object = self.object(offset, OBJECT_ENTRY)?;
+
+        let seqnum = le64(object, 16)?;
+        let realtime = le64(object, 24)?;
+        let monotonic = le64(object, 32)?;
+        let boot_id = hex(&read_id(object, 40)?);
+        let xor_hash = le64(object, 56)?;
+
+        let item_size = if self.header.is_compact() { 4 } else { 16 };
+        let items = tail(object, 64)?;
+
+        let mut fields = Vec::with_capacity(items.len() / item_size);
+
+        for item in items.chunks_exact(item_size) {
+            let data_offset = if self.header.is_compact() {
+                le32(item, 0)? as u64
+            } else {
+                le64(item, 0)?
+            };
+
+            let payload = self.data_payload(data_offset)?;
+
+            if let Some(i) = payload.iter().position(|b| *b == b'=') {
+                fields.push((
+                    String::from_utf8_lossy(&payload[..i]).into_owned(),
+                    payload[i + 1..].to_vec(),
+                ));
+            }
+        }
+
+        let cursor = format!(
+            "s={};i={:x};b={};m={:x};t={:x};x={:x}",
+            hex(&self.header.seqnum_id),
+            seqnum,
+            boot_id,
+            monotonic,
+            realtime,
+            xor_hash
+        );
+
+        Ok(Entry {
+            seqnum,
+            realtime,
+            monotonic,
+            boot_id,
+            cursor,
+            fields,
+        })
+    }
+
+    /// Offsets stored in a chain of entry arrays, up to `n` of them
+    fn entry_array_items(&self, mut offset: u64, n: u64) -> Result<Vec<u64>, JournalError> {
+        let item_size = if self.header.is_compact() { 4 } else { 8 };
+        let mut items = vec![];
+
+        while offset != 0 && (items.len() as u64) < n {
+            let object = self.object(offset, OBJECT_ENTRY_ARRAY)?;
+
+            for item in tail(object, 24)?.chunks_exact(item_size) {
+                let entry_offset = if self.header.is_compact() {
+                    le32(item, 0)? as u64
+                } else {
+                    le64(item, 0)?
+                };
+
+                if entry_offset == 0 || items.len() as u64 >= n {
+                    break;
+                }
+
+                items.push(entry_offset);
+            }
+
+            offset = next_offset(offset, le64(object, 16)?)?;
+        }
+
+        Ok(items)
+    }
+
+    /// Payload of a data object, decompressed if needed
+    fn data_payload(&self, offset: u64) -> Result<Cow<'_, [u8]>, JournalError> {
+        let object = self.object(offset, OBJECT_DATA)?;
+        let payload_offset = if self.header.is_compact() { 72 } else { 64 };
+        let payload = tail(object, payload_offset)?;
+
+        let flags = ObjectFlags::from_bits_truncate(object[1]);
+
+        if flags.contains(ObjectFlags::COMPRESSED_ZSTD) {
+            let decoder = ruzstd::decoding::StreamingDecoder::new(payload)
+                .map_err(|e| JournalError::InvalidFile(format!("zstd: {}", e)))?;
+            read_limited(decoder, "zstd").map(Cow::Owned)
+        } else if flags.contains(ObjectFlags::COMPRESSED_XZ) {
+            xz_decompress_limited(payload, MAX_DECOMPRESSED_SIZE).map(Cow::Owned)
+        } else if flags.contains(ObjectFlags::COMPRESSED_LZ4) {
+            // The uncompressed size precedes the LZ4 block
+            let size = le64(payload, 0)? as usize;
+            if size > MAX_DECOMPRESSED_SIZE {
+                return Err(JournalError::InvalidFile(format!(
+                    "lz4 payload of {} bytes is too large",
+                    size
+                )));
+            }
+
+            lz4_flex::block::decompress(&payload[8..], size)
+                .map(Cow::Owned)
+                .map_err(|e| JournalError::InvalidFile(format!("lz4: {}", e)))
+        } else {
+            Ok(Cow::Borrowed(payload))
+        }
+    }
+
+    /// Whole object at the offset, checking its type and bounds
+    fn object(&self, offset: u64, object_type: u8) -> Result<&[u8], JournalError> {
+        if offset % 8 != 0 || offset < self.header.header_size {
+            return Err(JournalError::InvalidFile(format!(
+                "invalid object offset {}",
+                offset
+            )));
+        }
+
+        let header = slice(
+            &self.data,
+            offset,
+            offset.saturating_add(OBJECT_HEADER_SIZE),
+        )?;
+        let size = le64(header, 8)?;
+
+        if header[0] != object_type || size < OBJECT_HEADER_SIZE {
+            return Err(JournalError::InvalidFile(format!(
+                "unexpected object of type {} and size {} at {}",
+                header[0], size, offset
+            )));
+        }
+
+        slice(&self.data, offset, offset.saturating_add(size))
+    }
+}
+
+/// Chains are only linked forward, anything else is a loop in a corrupted file
+fn next_offset(current: u64, next: u64) -> Result<u64, JournalError> {
+    if next != 0 && next <= current {
+        return Err(JournalError::InvalidFile(format!(
+            "object at {} links back to {}",
+            current, next
+        )));
+    }
+
+    Ok(next)
+}
+
+fn read_limited<R: Read>(reader: R, codec: &str) -> Result<Vec<u8>, JournalError> {
+    let mut out = vec![];
+    reader
+        .take(MAX_DECOMPRESSED_SIZE as u64 + 1)
+        .read_to_end(&mut out)
+        .map_err(|e| JournalError::InvalidFile(format!("{}: {}", codec, e)))?;
+
+    if out.len() > MAX_DECOMPRESSED_SIZE {
+        return Err(JournalError::InvalidFile(format!(
+            "{} payload is too large",
+            codec
+        )));
+    }
+
+    Ok(out)
+}
+
+/// lzma-rs has no bound on the output, the writer fails once it grows past the limit
+fn xz_decompress_limited(payload: &[u8], limit: usize) -> Result<Vec<u8>, JournalError> {
+    let mut out = LimitedWriter {
+        data: vec![],
+        limit,
+    };
+    lzma_rs::xz_decompress(&mut &payload[..], &mut out)
+        .map_err(|e| JournalError::InvalidFile(format!("xz: {}", e)))?;
+
+    Ok(out.data)
+}
+
+struct LimitedWriter {
+    data: Vec<u8>,
+    limit: usize,
+}
+
+impl Write for LimitedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.data.len() + buf.len() > self.limit {
+            return Err(std::io::Error::other("payload is too large"));
+        }
+
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn tail(data: &[u8], start: u64) -> Result<&[u8], JournalError> {
+    slice(data, start, data.len() as u64)
+}
+
+fn le32(data: &[u8], offset: u64) -> Result<u32, JournalError> {
+    let bytes = slice(data, offset, offset.saturating_add(4))?;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hash::{jenkins_hash64, siphash24};
+    use super::*;
+
+    #[derive(Clone, Copy)]
+    enum Compression {
+        None,
+        Xz,
+        Lz4,
+        Zstd,
+    }
+
+    const HEADER_SIZE: usize = 272;
+    const DATA_BUCKETS: usize = 4;
+    const FIELD_BUCKETS: usize = 2;
+    const FILE_ID: [u8; 16] = [7; 16];
+
+    /// Writes the minimal set of objects libsystemd would write for the entries
+    struct Builder {
+        data: Vec<u8>,
+        compact: bool,
+        keyed: bool,
+        data_table: u64,
+        field_table: u64,
+        /// Payload, offset and entries of each data object
+        data_objects: Vec<(Vec<u8>, u64, Vec<u64>)>,
+        fields: Vec<(String, u64)>,
+        entries: Vec<u64>,
+        n_objects: u64,
+    }
+
+    impl Builder {
+        fn new(compact: bool, keyed: bool) -> Builder {
+            let mut b = Builder {
+                data: vec![0; HEADER_SIZE],
+                compact,
+                keyed,
+                data_table: 0,
+                field_table: 0,
+                data_objects: vec![],
+                fields: vec![],
+                entries: vec![],
+                n_objects: 0,
+            };
+
+            b.field_table = b.append(5, 0, &[0; FIELD_BUCKETS * 16]) + 16;
+            b.data_table = b.append(4, 0, &[0; DATA_BUCKETS * 16]) + 16;
+
+            b
+        }
+
+        fn hash(&self, payload: &[u8]) -> u64 {
+            if self.keyed {
+                siphash24(payload, &FILE_ID)
+            } else {
+                jenkins_hash64(payload)
+            }
+        }
+
+        fn append(&mut self, object_type: u8, flags: u8, body: &[u8]) -> u64 {
+            let offset = self.data.len() as u64;
+            self.data.extend([object_type, flags, 0, 0, 0, 0, 0, 0]);
+            self.data.extend((16 + body.len() as u64).to_le_bytes());
+            self.data.extend(body);
+            self.data.resize(self.data.len().div_ceil(8) * 8, 0);
+            self.n_objects += 1;
+
+            offset
+        }
+
+        fn put(&mut self, offset: u64, value: u64) {
+            let o = offset as usize;
+            self.data[o..o + 8].copy_from_slice(&value.to_le_bytes());
+        }
+
+        fn get(&self, offset: u64) -> u64 {
+            le64(&self.data, offset).unwrap()
+        }
+
+        /// Links the object at the end of the hash chain of its bucket
+        fn link(&mut self, table: u64, buckets: usize, hash: u64, offset: u64) {
+            let bucket = table + (hash % buckets as u64) * 16;
+            let mut last = self.get(bucket);
+
+            if last == 0 {
+                self.put(bucket, offset);
+                return;
+            }
+
+            while self.get(last + 24) != 0 {
+                last = self.get(last + 24);
+            }
+
+            self.put(last + 24, offset);
+        }
+
+        fn field(&mut self, name: &str) {
+            if self.fields.iter().any(|(n, _)| n == name) {
+                return;
+            }
+
+            let hash = self.hash(name.as_bytes());
+            let mut body = hash.to_le_bytes().to_vec();
+            body.extend([0; 16]);
+            body.extend(name.as_bytes());
+
+            let offset = self.append(2, 0, &body);
+            self.link(self.field_table, FIELD_BUCKETS, hash, offset);
+            self.fields.push((name.to_owned(), offset));
+        }
+
+        fn data_object(&mut self, payload: &[u8], compression: Compression) -> u64 {
+            if let Some((_, offset, _)) = self.data_objects.iter().find(|(p, _, _)| p == payload) {
+                return *offset;
+            }
+
+            let (flags, stored) = match compression {
+                Compression::None => (0, payload.to_vec()),
+                Compression::Xz => {
+                    let mut out = vec![];
+                    lzma_rs::xz_compress(&mut &payload[..], &mut out).unwrap();
+                    (1, out)
+                }
+                Compression::Lz4 => {
+                    let mut out = (payload.len() as u64).to_le_bytes().to_vec();
+                    out.extend(lz4_flex::block::compress(payload));
+                    (2, out)
+                }
+                Compression::Zstd => (
+                    4,
+                    ruzstd::encoding::compress_to_vec(
+                        payload,
+                        ruzstd::encoding::CompressionLevel::Fastest,
+                    ),
+                ),
+            };
+
+            let hash = self.hash(payload);
+            // hash, next hash, next field, entry, entry array, number of entries
+            let mut body = hash.to_le_bytes().to_vec();
+            body.extend([0; 40]);
+            if self.compact {
+                body.extend([0; 8]);
+            }
+            body.extend(stored);
+
+            let offset = self.append(1, flags, &body);
+            self.link(self.data_table, DATA_BUCKETS, hash, offset);
+            self.data_objects.push((payload.to_vec(), offset, vec![]));
+
+            offset
+        }
+
+        fn entry(&mut self, realtime: u64, fields: &[(&str, &[u8], Compression)]) {
+            let mut items = vec![];
+
+            for (name, value, compression) in fields {
+                self.field(name);
+
+                let mut payload = name.as_bytes().to_vec();
+                payload.push(b'=');
+                payload.extend(*value);
+
+                let offset = self.data_object(&payload, *compression);
+                items.push((offset, self.hash(&payload)));
+            }
+
+            let seqnum = self.entries.len() as u64 + 1;
+            let mut body = seqnum.to_le_bytes().to_vec();
+            body.extend(realtime.to_le_bytes());
+            body.extend((realtime - 1_000).to_le_bytes());
+            body.extend([0xb0; 16]);
+            body.extend(items.iter().fold(0u64, |x, (_, h)| x ^ h).to_le_bytes());
+            for (offset, hash) in items.iter() {
+                if self.compact {
+                    body.extend((*offset as u32).to_le_bytes());
+                } else {
+                    body.extend(offset.to_le_bytes());
+                    body.extend(hash.to_le_bytes());
+                }
+            }
+
+            let entry_offset = self.append(3, 0, &body);
+            self.entries.push(entry_offset);
+
+            for (offset, _) in items {
+                let data = self
+                    .data_objects
+                    .iter_mut()
+                    .find(|(_, o, _)| *o == offset)
+                    .unwrap();
+                data.2.push(entry_offset);
+            }
+        }
+
+        /// Writes the items in chained arrays of `capacity` items, returns the first one
+        fn entry_arrays(&mut self, items: &[u64], capacity: usize) -> u64 {
+            let mut first = 0;
+            let mut previous = 0;
+
+            for chunk in items.chunks(capacity) {
+                let mut body = 0u64.to_le_bytes().to_vec();
+                for item in chunk {
+                    if self.compact {
+                        body.extend((*item as u32).to_le_bytes());
+                    } else {
+                        body.extend(item.to_le_bytes());
+                    }
+                }
+                // Unused slot at the end, like preallocated arrays
+                body.extend([0; 8]);
+
+                let offset = self.append(6, 0, &body);
+                if previous == 0 {
+                    first = offset;
+                } else {
+                    self.put(previous + 16, offset);
+                }
+                previous = offset;
+            }
+
+            first
+        }
+
+        fn build(mut self) -> Vec<u8> {
+            let entries = self.entries.clone();
+            let entry_array = self.entry_arrays(&entries, 2);
+
+            for i in 0..self.data_objects.len() {
+                let (_, offset, data_entries) = self.data_objects[i].clone();
+                self.put(offset + 40, data_entries[0]);
+                self.put(offset + 56, data_entries.len() as u64);
+                if data_entries.len() > 1 {
+                    let array = self.entry_arrays(&data_entries[1..], 1);
+                    self.put(offset + 48, array);
+                }
+            }
+
+            let mut incompatible = 0u32;
+            if self.keyed {
+                incompatible |= IncompatibleFlags::KEYED_HASH.bits();
+            }
+            if self.compact {
+                incompatible |= IncompatibleFlags::COMPACT.bits();
+            }
+
+            let h = &mut self.data;
+            h[0..8].copy_from_slice(header::SIGNATURE);
+            h[12..16].copy_from_slice(&incompatible.to_le_bytes());
+            h[24..40].copy_from_slice(&FILE_ID);
+            h[72..88].copy_from_slice(&[0x5e; 16]);
+
+            let field_table = self.field_table;
+            let data_table = self.data_table;
+            let n_objects = self.n_objects;
+            let n_entries = entries.len() as u64;
+            self.put(88, HEADER_SIZE as u64);
+            self.put(104, data_table);
+            self.put(112, (DATA_BUCKETS * 16) as u64);
+            self.put(120, field_table);
+            self.put(128, (FIELD_BUCKETS * 16) as u64);
+            self.put(144, n_objects);
+            self.put(152, n_entries);
+            self.put(176, entry_array);
+
+            self.data
+        }
+    }
+
+    fn build(compact: bool, keyed: bool) -> JournalFile {
+        let mut b = Builder::new(compact, keyed);
+        let long = "x".repeat(600);
+
+        b.entry(
+            1_000_000,
+            &[
+                ("MESSAGE", b"first", Compression::None),
+                ("PRIORITY", b"6", Compression::None),
+                ("_HOSTNAME", b"vm", Compression::None),
+            ],
+        );
+        b.entry(
+            2_000_000,
+            &[
+                ("MESSAGE", long.as_bytes(), Compression::Zstd),
+                ("PRIORITY", b"3", Compression::None),
+                ("_HOSTNAME", b"vm", Compression::None),
+            ],
+        );
+        b.entry(
+            3_000_000,
+            &[
+                ("MESSAGE", b"third \x01 binary", Compression::Xz),
+                ("PRIORITY", b"6", Compression::None),
+                ("_HOSTNAME", b"other", Compression::None),
+            ],
+        );
+        b.entry(
+            4_000_000,
+            &[
+                ("MESSAGE", b"fourth", Compression::Lz4),
+                ("PRIORITY", b"6", Compression::None),
+                ("_HOSTNAME", b"vm", Compression::None),
+            ],
+        );
+
+        JournalFile::from_bytes(b.build()).unwrap()
+    }
+
+    fn message(entry: &Entry) -> String {
+        let (_, value) = entry.fields.iter().find(|(f, _)| f == "MESSAGE").unwrap();
+        String::from_utf8_lossy(value).into_owned()
+    }
+
+    #[test]
+    fn reads_entries_in_every_layout() {
+        for (compact, keyed) in [(false, false), (false, true), (true, false), (true, true)] {
+            let file = build(compact, keyed);
+            assert_eq!(file.header().n_entries, 4);
+            assert_eq!(file.header().is_compact(), compact);
+
+            let entries = file.entries().unwrap();
+            let messages: Vec<String> = entries.iter().map(message).collect();
+            assert_eq!(
+                messages,
+                vec![
+                    "first".to_owned(),
+                    "x".repeat(600),
+                    "third \x01 binary".to_owned(),
+                    "fourth".to_owned()
+                ]
+            );
+
+            assert_eq!(entries[1].seqnum, 2);
+            assert_eq!(entries[1].realtime, 2_000_000);
+            assert_eq!(entries[1].monotonic, 1_999_000);
+            assert!(entries[1].cursor.starts_with(&format!(
+                "s={};i=2;b={};m=1e8098;t=1e8480;x=",
+                "5e".repeat(16),
+                "b0".repeat(16)
+            )));
+        }
+    }
+
+    #[test]
+    fn looks_up_data_through_the_hash_table() {
+        for (compact, keyed) in [(false, false), (true, true)] {
+            let file = build(compact, keyed);
+
+            let entries = file.entries_with("_HOSTNAME", b"vm").unwrap();
+            assert_eq!(entries.len(), 3);
+            assert_eq!(message(&entries[2]), "fourth");
+
+            let entries = file.entries_with("PRIORITY", b"3").unwrap();
+            assert_eq!(entries.len(), 1);
+
+            assert!(file.entries_with("PRIORITY", b"0").unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn lists_fields() {
+        let file = build(false, true);
+        assert_eq!(
+            file.fields().unwrap(),
+            vec!["MESSAGE", "PRIORITY", "_HOSTNAME"]
+        );
+    }
+
+    #[test]
+    fn bounds_decompressed_payloads() {
+        let mut payload = vec![];
+        lzma_rs::xz_compress(&mut &[0u8; 4096][..], &mut payload).unwrap();
+
+        assert_eq!(xz_decompress_limited(&payload, 4096).unwrap().len(), 4096);
+        assert!(matches!(
+            xz_decompress_limited(&payload, 4095),
+            Err(JournalError::InvalidFile(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_corrupted_files() {
+        assert!(JournalFile::from_bytes(b"not a journal".to_vec()).is_err());
+
+        let mut b = Builder::new(false, false);
+        b.entry(1_000, &[("MESSAGE", b"hello", Compression::None)]);
+        let mut data = b.build();
+
+        // Unknown incompatible flag
+        let mut unknown = data.clone();
+        unknown[15] = 0x80;
+        assert!(matches!(
+            JournalFile::from_bytes(unknown),
+            Err(JournalError::InvalidFile(_))
+        ));
+
+        // Entry array pointing outside of the file
+        let len = data.len() as u64;
+        data[176..184].copy_from_slice(&(len + 8).to_le_bytes());
+        let file = JournalFile::from_bytes(data.clone()).unwrap();
+        assert!(file.entries().is_err());
+
+        // Every truncation fails cleanly instead of panicking
+        for len in 0..data.len() {
+            if let Ok(file) = JournalFile::from_bytes(data[..len].to_vec()) {
+                let _ = file.entries();
+                let _ = file.fields();
+                let _ = file.entries_with("MESSAGE", b"hello");
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+/// Where the journal entries are read from
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum JournalSource {
+    /// Journal of the running system and the current user
+    #[default]
+    Local,
+    /// Journal namespaces of the running system, named like `journalctl --namespace`:
+    /// `*` for all of them and `+name` for a namespace along with the default one
+    Namespace { namespace: String },
+    /// All journal files in a directory, e.g. a copy of /var/log/journal/<machine-id>
+    Directory { path: String },
+    /// A set of individual .journal files
+    Files { paths: Vec<String> },
+    /// A `journalctl -o export` or `-o json` dump, read into memory
+    Import { path: String },
+}
+
+impl JournalSource {
+    /// Journal of the running system, in any namespace
+    pub fn is_local(&self) -> bool {
+        matches!(self, JournalSource::Local | JournalSource::Namespace { .. })
+    }
+
+    /// The same source reading every namespace of the running system, for the queries
+    /// selecting namespaces with `_NAMESPACE` matches
+    pub fn with_all_namespaces(&self) -> JournalSource {
+        match self {
+            JournalSource::Local => JournalSource::Namespace {
+                namespace: "*".into(),
+            },
+            source => source.clone(),
+        }
+    }
+
+    /// Whether the entries of a namespace, None for the default one, are read
+    /// from the running system
+    pub fn includes_namespace(&self, namespace: Option<&str>) -> bool {
+        match self {
+            JournalSource::Local => namespace.is_none(),
+            JournalSource::Namespace {
+                namespace: selected,
+            } => match (selected.as_str(), namespace) {
+                ("*", _) => true,
+                (selected, None) => selected.starts_with('+'),
+                (selected, Some(namespace)) => {
+                    selected.strip_prefix('+').unwrap_or(selected) == namespace
+                }
+            },
+            JournalSource::Directory { .. }
+            | JournalSource::Files { .. }
+            | JournalSource::Import { .. } => false,
+        }
+    }
+}
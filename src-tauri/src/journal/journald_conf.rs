@@ -0,0 +1,295 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Main configuration file, the first one found is read
+const CONF_FILES: [&str; 4] = [
+    "etc/systemd/journald.conf",
+    "run/systemd/journald.conf",
+    "usr/local/lib/systemd/journald.conf",
+    "usr/lib/systemd/journald.conf",
+];
+
+/// Directories of drop-ins, a drop-in in a former one masks the ones of the same name after it
+const DROP_IN_DIRS: [&str; 4] = [
+    "etc/systemd/journald.conf.d",
+    "run/systemd/journald.conf.d",
+    "usr/local/lib/systemd/journald.conf.d",
+    "usr/lib/systemd/journald.conf.d",
+];
+
+/// Files journald keeps at most in each storage when not configured
+const DEFAULT_MAX_FILES: u64 = 100;
+/// A month, how long journald writes to a file before rotating it when not configured
+const DEFAULT_MAX_FILE_SEC: u64 = 2_629_800;
+
+/// Settings of journald deciding how long entries are kept, read like journald does
+/// from journald.conf and its drop-ins
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct JournaldConfig {
+    /// Files read, in the order they were applied
+    pub files: Vec<String>,
+    /// `volatile`, `persistent`, `auto` or `none`
+    pub storage: String,
+    /// `yes`, `no` or the size from which entries are compressed
+    pub compress: String,
+    /// Limits of the persistent journal in /var/log/journal
+    pub system: StorageLimits,
+    /// Limits of the volatile journal in /run/log/journal
+    pub runtime: StorageLimits,
+    /// Entries older than this many seconds are removed, None to keep them
+    pub max_retention_sec: Option<u64>,
+    /// Files are rotated after this many seconds, None to only rotate by size
+    pub max_file_sec: Option<u64>,
+}
+
+/// Sizes are in bytes, None when journald computes them from the size of the file system
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageLimits {
+    /// None for 10% of the file system, up to 4G
+    pub max_use: Option<u64>,
+    /// None for 15% of the file system, up to 4G
+    pub keep_free: Option<u64>,
+    /// None for an eighth of `max_use`, up to 128M
+    pub max_file_size: Option<u64>,
+    pub max_files: u64,
+}
+
+/// Reads the configuration of journald under `root`, `/` for the running system
+pub(crate) fn read_journald_conf(root: &str) -> JournaldConfig {
+    let root = Path::new(root);
+    let mut files: Vec<String> = vec![];
+
+    if let Some(conf) = CONF_FILES
+        .iter()
+        .map(|f| root.join(f))
+        .find(|f| f.is_file())
+    {
+        files.push(conf.to_string_lossy().into_owned());
+    }
+
+    let mut drop_ins: Vec<(String, String)> = vec![];
+    for dir in DROP_IN_DIRS.iter().map(|d| root.join(d)) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.ends_with(".conf") && !drop_ins.iter().any(|(n, _)| *n == name) {
+                drop_ins.push((name, entry.path().to_string_lossy().into_owned()));
+            }
+        }
+    }
+    drop_ins.sort();
+    files.extend(drop_ins.into_iter().map(|(_, path)| path));
+
+    let mut settings = HashMap::new();
+    for file in files.iter() {
+        match std::fs::read_to_string(file) {
+            Ok(text) => parse_settings(&text, &mut settings),
+            Err(e) => warn!("Could not read {}: {}", file, e),
+        }
+    }
+
+    JournaldConfig::from_settings(files, &settings)
+}
+
+/// Assignments of the `[Journal]` section, a later one overrides the former and
+/// an empty one resets the setting to its default
+fn parse_settings(text: &str, settings: &mut HashMap<String, String>) {
+    let mut in_journal_section = false;
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            in_journal_section = line == "[Journal]";
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+
+        if !in_journal_section {
+            continue;
+        }
+
+        let (key, value) = (key.trim(), value.trim());
+        if value.is_empty() {
+            settings.remove(key);
+        } else {
+            settings.insert(key.to_owned(), value.to_owned());
+        }
+    }
+}
+
+impl JournaldConfig {
+    fn from_settings(files: Vec<String>, settings: &HashMap<String, String>) -> JournaldConfig {
+        let value = |key: &str, parse: fn(&str) -> Option<u64>| {
+            settings.get(key).and_then(|v| parsed(key, v, parse))
+        };
+        let limits = |prefix: &str| StorageLimits {
+            max_use: value(&format!("{}MaxUse", prefix), parse_size),
+            keep_free: value(&format!("{}KeepFree", prefix), parse_size),
+            max_file_size: value(&format!("{}MaxFileSize", prefix), parse_size),
+            max_files: value(&format!("{}MaxFiles", prefix), |v| v.parse().ok())
+                .unwrap_or(DEFAULT_MAX_FILES),
+        };
+
+        JournaldConfig {
+            files,
+            storage: settings.get("Storage").cloned().unwrap_or("auto".into()),
+            compress: settings.get("Compress").cloned().unwrap_or("yes".into()),
+            system: limits("System"),
+            runtime: limits("Runtime"),
+            // Zero turns the limits off
+            max_retention_sec: value("MaxRetentionSec", parse_seconds).filter(|s| *s > 0),
+            max_file_sec: value("MaxFileSec", parse_seconds)
+                .or(Some(DEFAULT_MAX_FILE_SEC))
+                .filter(|s| *s > 0),
+        }
+    }
+}
+
+/// Invalid values are ignored by journald, which keeps the default
+fn parsed(key: &str, value: &str, parse: fn(&str) -> Option<u64>) -> Option<u64> {
+    let result = parse(value);
+    if result.is_none() {
+        warn!("Ignoring the invalid value {} of {}", value, key);
+    }
+
+    result
+}
+
+/// Size in bytes like `4G` or `1.5M`, with suffixes in powers of 1024
+fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, suffix) = value.split_at(split);
+
+    let exponent = match suffix.trim() {
+        "" | "B" => 0,
+        "K" => 1,
+        "M" => 2,
+        "G" => 3,
+        "T" => 4,
+        "P" => 5,
+        "E" => 6,
+        _ => return None,
+    };
+
+    let number: f64 = number.parse().ok()?;
+
+    Some((number * 1024f64.powi(exponent)) as u64)
+}
+
+/// Time span in seconds like `1month`, `2w` or `1h 30min`, plain numbers are seconds
+fn parse_seconds(value: &str) -> Option<u64> {
+    const MINUTE: f64 = 60.0;
+    const HOUR: f64 = 60.0 * MINUTE;
+    const DAY: f64 = 24.0 * HOUR;
+
+    let mut rest = value.trim();
+    let mut total = 0.0;
+
+    if rest.is_empty() {
+        return None;
+    }
+
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..number_end].parse().ok()?;
+        rest = rest[number_end..].trim_start();
+
+        let unit_end = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let multiplier = match &rest[..unit_end] {
+            "" | "s" | "sec" | "second" | "seconds" => 1.0,
+            "us" | "usec" => 0.000_001,
+            "ms" | "msec" => 0.001,
+            "m" | "min" | "minute" | "minutes" => MINUTE,
+            "h" | "hr" | "hour" | "hours" => HOUR,
+            "d" | "day" | "days" => DAY,
+            "w" | "week" | "weeks" => 7.0 * DAY,
+            "M" | "month" | "months" => 30.4375 * DAY,
+            "y" | "year" | "years" => 365.25 * DAY,
+            _ => return None,
+        };
+
+        total += number * multiplier;
+        rest = rest[unit_end..].trim_start();
+    }
+
+    Some(total as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StorageLimits, parse_seconds, parse_size, read_journald_conf};
+
+    #[test]
+    fn sizes_and_time_spans() {
+        assert_eq!(parse_size("4G"), Some(4 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size("1.5M"), Some(1_572_864));
+        assert_eq!(parse_size("4096"), Some(4096));
+        assert_eq!(parse_size("10%"), None);
+
+        assert_eq!(parse_seconds("3600"), Some(3600));
+        assert_eq!(parse_seconds("2weeks"), Some(1_209_600));
+        assert_eq!(parse_seconds("1h 30min"), Some(5400));
+        assert_eq!(parse_seconds("1month"), Some(2_629_800));
+        assert_eq!(parse_seconds("1year"), Some(31_557_600));
+        assert_eq!(parse_seconds("soon"), None);
+    }
+
+    #[test]
+    fn drop_ins_override_the_main_file() {
+        let config = read_journald_conf("./tests/fixtures/journald");
+
+        assert_eq!(
+            config.files,
+            vec![
+                "./tests/fixtures/journald/etc/systemd/journald.conf",
+                "./tests/fixtures/journald/usr/lib/systemd/journald.conf.d/10-size.conf",
+                "./tests/fixtures/journald/etc/systemd/journald.conf.d/50-retention.conf",
+            ]
+        );
+        assert_eq!(config.storage, "persistent");
+        assert_eq!(config.compress, "yes");
+        assert_eq!(
+            config.system,
+            StorageLimits {
+                max_use: Some(500 * 1024 * 1024),
+                keep_free: None,
+                max_file_size: Some(64 * 1024 * 1024),
+                max_files: 100,
+            }
+        );
+        assert_eq!(config.runtime.max_use, Some(64 * 1024 * 1024));
+        assert_eq!(config.max_retention_sec, Some(1_209_600));
+        // Reset to the default by an empty assignment
+        assert_eq!(config.max_file_sec, Some(2_629_800));
+    }
+
+    #[test]
+    fn defaults_without_configuration() {
+        let config = read_journald_conf("./tests/fixtures/no-such-root");
+
+        assert!(config.files.is_empty());
+        assert_eq!(config.storage, "auto");
+        assert_eq!(config.system.max_use, None);
+        assert_eq!(config.runtime.max_files, 100);
+        assert_eq!(config.max_retention_sec, None);
+    }
+}
@@ -0,0 +1,216 @@
+use super::journal_fields;
+use serde::Deserialize;
+
+/// Devices the kernel messages are narrowed down to. Values of the same field
+/// are ORed and the fields ANDed, e.g. the `usb` subsystem of either device.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct KernelFilter {
+    /// Values of `_KERNEL_SUBSYSTEM`, e.g. `usb` or `pci`
+    pub subsystems: Vec<String>,
+    /// Values of `_KERNEL_DEVICE`, e.g. `b8:0` or `+usb:1-1.4`
+    pub devices: Vec<String>,
+    /// Values of `_UDEV_SYSNAME`, e.g. `sda`
+    pub sysnames: Vec<String>,
+    /// Values of `_UDEV_DEVNODE`, e.g. `/dev/sda`
+    pub devnodes: Vec<String>,
+}
+
+impl KernelFilter {
+    pub(crate) fn matches(&self) -> Vec<String> {
+        [
+            (journal_fields::KERNEL_SUBSYSTEM, &self.subsystems),
+            (journal_fields::KERNEL_DEVICE, &self.devices),
+            (journal_fields::UDEV_SYSNAME, &self.sysnames),
+            (journal_fields::UDEV_DEVNODE, &self.devnodes),
+        ]
+        .into_iter()
+        .flat_map(|(field, values)| values.iter().map(move |v| format!("{}={}", field, v)))
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KernelFilter;
+    use crate::journal::testing::{self, entry};
+    use crate::journal::{
+        EntryWriter, ExportFormat, ImportedEntry, Journal, MemoryJournal, QueryBuilder,
+    };
+
+    /// The i-th second of the journal, i milliseconds after its boot
+    fn message(i: u64, boot: &str, transport: &str, fields: &[(&str, &str)]) -> ImportedEntry {
+        let message = format!("message {}", i);
+        let mut all = vec![
+            ("MESSAGE", message.as_str()),
+            ("PRIORITY", "6"),
+            ("_BOOT_ID", boot),
+            ("_TRANSPORT", transport),
+        ];
+        all.extend_from_slice(fields);
+
+        ImportedEntry {
+            monotonic: i * 1_000,
+            cursor: format!("c{}", i),
+            ..entry(i * 1_000_000, &all)
+        }
+    }
+
+    fn journal() -> Journal<MemoryJournal> {
+        testing::journal(vec![
+            message(1, "b1", "kernel", &[("_KERNEL_SUBSYSTEM", "usb")]),
+            message(2, "b2", "kernel", &[]),
+            message(
+                3,
+                "b2",
+                "kernel",
+                &[
+                    ("_KERNEL_SUBSYSTEM", "usb"),
+                    ("_KERNEL_DEVICE", "+usb:1-1.4"),
+                ],
+            ),
+            message(4, "b2", "journal", &[("_KERNEL_SUBSYSTEM", "usb")]),
+            message(
+                5,
+                "b2",
+                "kernel",
+                &[
+                    ("_KERNEL_SUBSYSTEM", "block"),
+                    ("_KERNEL_DEVICE", "b8:0"),
+                    ("_UDEV_SYSNAME", "sda"),
+                    ("_UDEV_DEVNODE", "/dev/sda"),
+                ],
+            ),
+        ])
+    }
+
+    fn rows(filter: KernelFilter) -> Vec<Vec<String>> {
+        let q = QueryBuilder::default()
+            .with_fields(vec!["__MONOTONIC".into(), "MESSAGE".into()])
+            .with_priority_above_or_equal_to(7)
+            .with_kernel(filter)
+            .build();
+
+        // Without the cursor added to the fields
+        journal()
+            .query_logs(&q)
+            .unwrap()
+            .rows
+            .into_iter()
+            .map(|r| r[..2].to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn kernel_messages_of_the_last_boot() {
+        assert_eq!(
+            rows(KernelFilter::default()),
+            vec![
+                vec!["5000", "message 5"],
+                vec!["3000", "message 3"],
+                vec!["2000", "message 2"],
+            ]
+        );
+    }
+
+    #[test]
+    fn narrowed_down_by_device() {
+        let usb = KernelFilter {
+            subsystems: vec!["usb".into()],
+            ..KernelFilter::default()
+        };
+        assert_eq!(rows(usb), vec![vec!["3000", "message 3"]]);
+
+        let disk_or_usb = KernelFilter {
+            devices: vec!["b8:0".into(), "+usb:1-1.4".into()],
+            ..KernelFilter::default()
+        };
+        assert_eq!(
+            rows(disk_or_usb),
+            vec![vec!["5000", "message 5"], vec!["3000", "message 3"]]
+        );
+
+        let sda_not_usb = KernelFilter {
+            subsystems: vec!["usb".into()],
+            devnodes: vec!["/dev/sda".into()],
+            ..KernelFilter::default()
+        };
+        assert!(rows(sda_not_usb).is_empty());
+    }
+
+    #[test]
+    fn exports_the_monotonic_time() {
+        let q = QueryBuilder::default()
+            .with_priority_above_or_equal_to(7)
+            .with_kernel(KernelFilter {
+                sysnames: vec!["sda".into()],
+                ..KernelFilter::default()
+            })
+            .build();
+
+        let mut out = vec![];
+        let mut writer = EntryWriter::new(&mut out, ExportFormat::JournalExport, vec![]).unwrap();
+        journal().export(&q, &mut writer).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with(
+            "__CURSOR=c5\n__REALTIME_TIMESTAMP=5000000\n__MONOTONIC_TIMESTAMP=5000\n"
+        ));
+    }
+
+    /// Written by journald under another boot id than the running one, read through
+    /// libsystemd or the journal file reader
+    #[cfg(any(feature = "libsystemd", feature = "journal-file"))]
+    fn past_boot_journal() -> Journal {
+        Journal::open_source(&crate::journal::JournalSource::Files {
+            paths: vec!["./tests/fixtures/journal-files/past-boot.journal".into()],
+        })
+        .unwrap()
+    }
+
+    #[cfg(any(feature = "libsystemd", feature = "journal-file"))]
+    #[test]
+    fn monotonic_time_of_a_past_boot() {
+        let journal = past_boot_journal();
+        let q = QueryBuilder::default()
+            .with_fields(vec!["__MONOTONIC".into(), "MESSAGE".into()])
+            .with_priority_above_or_equal_to(7)
+            .with_transports(vec!["journal".into()])
+            .build();
+
+        let rows: Vec<Vec<String>> = journal
+            .query_logs(&q)
+            .unwrap()
+            .rows
+            .into_iter()
+            .map(|r| r[..2].to_vec())
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["11272232668", "past boot 2"],
+                vec!["11272232653", "past boot 1"],
+                vec!["11272232068", "past boot 0"],
+            ]
+        );
+    }
+
+    #[cfg(any(feature = "libsystemd", feature = "journal-file"))]
+    #[test]
+    fn exports_the_monotonic_time_of_a_past_boot() {
+        let journal = past_boot_journal();
+        let q = QueryBuilder::default()
+            .with_priority_above_or_equal_to(7)
+            .with_transports(vec!["journal".into()])
+            .build();
+
+        let mut out = vec![];
+        let mut writer = EntryWriter::new(&mut out, ExportFormat::JournalExport, vec![]).unwrap();
+        assert_eq!(journal.export(&q, &mut writer).unwrap(), 3);
+
+        let out = String::from_utf8(out).unwrap();
+        for monotonic in ["11272232068", "11272232653", "11272232668"] {
+            assert!(out.contains(&format!("\n__MONOTONIC_TIMESTAMP={}\n", monotonic)));
+        }
+    }
+}
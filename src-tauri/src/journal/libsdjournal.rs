@@ -1,17 +1,8 @@
+use super::backend::WakeupEvent;
+use super::error::JournalError;
 use super::libsdjournal_bindings;
 use libc::{c_char, c_void, size_t};
-use serde::Serialize;
 use std::ffi::{CStr, CString};
-use thiserror::Error;
-
-#[derive(Error, Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub enum JournalError {
-    #[error("Internal error while invoking systemd. Error Code: {0}")]
-    Internal(i32),
-    #[error("Reached the end of the cursor")]
-    EndOfFile,
-}
 
 pub fn sd_journal_open(sd_journal: &mut *mut c_void, flags: u32) -> Result<(), JournalError> {
     let ret: libc::c_int;
@@ -26,6 +17,79 @@ pub fn sd_journal_open(sd_journal: &mut *mut c_void, flags: u32) -> Result<(), J
     Ok(())
 }
 
+/// Opens the journal of a namespace, or of the default namespace when None
+pub fn sd_journal_open_namespace(
+    sd_journal: &mut *mut c_void,
+    namespace: Option<&str>,
+    flags: u32,
+) -> Result<(), JournalError> {
+    let c_namespace = namespace
+        .map(CString::new)
+        .transpose()
+        .map_err(|_| JournalError::Internal(-libc::EINVAL))?;
+    let ret: libc::c_int;
+
+    unsafe {
+        ret = libsdjournal_bindings::sd_journal_open_namespace(
+            sd_journal,
+            c_namespace
+                .as_ref()
+                .map_or(std::ptr::null(), |n| n.as_ptr()),
+            flags,
+        );
+    }
+    if ret != 0 {
+        return Err(JournalError::Internal(ret));
+    }
+
+    Ok(())
+}
+
+pub fn sd_journal_open_directory(
+    sd_journal: &mut *mut c_void,
+    path: &str,
+    flags: u32,
+) -> Result<(), JournalError> {
+    let c_path = CString::new(path).map_err(|_| JournalError::Internal(-libc::EINVAL))?;
+    let ret: libc::c_int;
+
+    unsafe {
+        ret = libsdjournal_bindings::sd_journal_open_directory(sd_journal, c_path.as_ptr(), flags);
+    }
+    if ret != 0 {
+        return Err(JournalError::Internal(ret));
+    }
+
+    Ok(())
+}
+
+pub fn sd_journal_open_files(
+    sd_journal: &mut *mut c_void,
+    paths: &[String],
+    flags: u32,
+) -> Result<(), JournalError> {
+    let c_paths = paths
+        .iter()
+        .map(|p| CString::new(p.as_str()))
+        .collect::<Result<Vec<CString>, _>>()
+        .map_err(|_| JournalError::Internal(-libc::EINVAL))?;
+
+    // libsystemd expects a NULL terminated array of paths
+    let mut c_path_ptrs: Vec<*const c_char> = c_paths.iter().map(|p| p.as_ptr()).collect();
+    c_path_ptrs.push(std::ptr::null());
+
+    let ret: libc::c_int;
+
+    unsafe {
+        ret = libsdjournal_bindings::sd_journal_open_files(sd_journal, c_path_ptrs.as_ptr(), flags);
+    }
+    if ret != 0 {
+        return Err(JournalError::Internal(ret));
+    }
+
+    Ok(())
+}
+
 pub fn sd_journal_close(sd_journal: *mut c_void) {
     unsafe {
         libsdjournal_bindings::sd_journal_close(sd_journal);
@@ -106,25 +170,15 @@ pub fn sd_journal_get_data(sd_journal: *mut c_void, field: &str) -> Result<Strin
         return Err(JournalError::Internal(ret));
     }
 
-    let result = unsafe {
-        match CStr::from_ptr(data as *mut c_char).to_str() {
-            Ok(s) => {
-                let s = String::from(s);
-                let remove = format!("{}=", field);
-                if let Some(value) = s.strip_prefix(&remove) {
-                    return Ok(value.to_string());
-                }
-                Ok(s)
-            }
-            Err(_) => Err(-1),
-        }
-    };
+    // The data is not NUL terminated, only the length tells where it ends
+    let bytes = unsafe { std::slice::from_raw_parts(data as *const u8, length) };
+    let value = String::from_utf8_lossy(bytes);
+    let prefix = format!("{}=", field);
 
-    if let Err(e) = result {
-        return Err(JournalError::Internal(e));
+    match value.strip_prefix(&prefix) {
+        Some(v) => Ok(v.to_owned()),
+        None => Ok(value.into_owned()),
     }
-
-    Ok(result.unwrap())
 }
 
 pub fn sd_journal_add_match(sd_journal: *mut c_void, data: String) -> Result<(), JournalError> {
@@ -219,6 +273,30 @@ pub fn sd_journal_get_realtime_usec(
     Ok(())
 }
 
+/// Time since the boot of the entry. The boot id is read too, without it libsystemd fails with
+/// -ESTALE for the entries of any boot but the running one.
+pub fn sd_journal_get_monotonic_usec(
+    sd_journal: *mut c_void,
+    microseconds: &mut u64,
+) -> Result<(), JournalError> {
+    let ret: libc::c_int;
+    let mut boot_id = libsdjournal_bindings::SdId128 { qwords: [0; 2] };
+
+    unsafe {
+        ret = libsdjournal_bindings::sd_journal_get_monotonic_usec(
+            sd_journal,
+            microseconds,
+            &mut boot_id,
+        );
+    }
+
+    if ret < 0 {
+        return Err(JournalError::Internal(ret));
+    }
+
+    Ok(())
+}
+
 pub fn sd_journal_seek_realtime_usec(
     sd_journal: *mut c_void,
     microseconds: u64,
@@ -236,10 +314,255 @@ pub fn sd_journal_seek_realtime_usec(
     Ok(())
 }
 
+pub fn sd_journal_get_cursor(sd_journal: *mut c_void) -> Result<String, JournalError> {
+    let mut cursor: *mut c_char = std::ptr::null_mut();
+    let ret: libc::c_int;
+
+    unsafe {
+        ret = libsdjournal_bindings::sd_journal_get_cursor(sd_journal, &mut cursor);
+    }
+
+    if ret < 0 {
+        return Err(JournalError::Internal(ret));
+    }
+
+    // The cursor is allocated by libsystemd and has to be freed by the caller
+    let result = unsafe {
+        let value = CStr::from_ptr(cursor).to_str().map(String::from);
+        libc::free(cursor as *mut c_void);
+        value
+    };
+
+    result.map_err(|_| JournalError::Internal(-1))
+}
+
+pub fn sd_journal_seek_cursor(sd_journal: *mut c_void, cursor: &str) -> Result<(), JournalError> {
+    let c_cursor = CString::new(cursor).map_err(|_| JournalError::Internal(-libc::EINVAL))?;
+    let ret: libc::c_int;
+
+    unsafe {
+        ret = libsdjournal_bindings::sd_journal_seek_cursor(sd_journal, c_cursor.as_ptr());
+    }
+
+    if ret < 0 {
+        return Err(JournalError::Internal(ret));
+    }
+
+    Ok(())
+}
+
+pub fn sd_journal_test_cursor(sd_journal: *mut c_void, cursor: &str) -> Result<bool, JournalError> {
+    let c_cursor = CString::new(cursor).map_err(|_| JournalError::Internal(-libc::EINVAL))?;
+    let ret: libc::c_int;
+
+    unsafe {
+        ret = libsdjournal_bindings::sd_journal_test_cursor(sd_journal, c_cursor.as_ptr());
+    }
+
+    if ret < 0 {
+        return Err(JournalError::Internal(ret));
+    }
+
+    Ok(ret > 0)
+}
+
+pub fn sd_journal_query_unique(sd_journal: *mut c_void, field: &str) -> Result<(), JournalError> {
+    let c_field = CString::new(field).map_err(|_| JournalError::Internal(-libc::EINVAL))?;
+    let ret: libc::c_int;
+
+    unsafe {
+        ret = libsdjournal_bindings::sd_journal_query_unique(sd_journal, c_field.as_ptr());
+    }
+
+    if ret < 0 {
+        return Err(JournalError::Internal(ret));
+    }
+
+    Ok(())
+}
+
+/// Returns the next unique value of the field set by sd_journal_query_unique,
+/// without the field name
+pub fn sd_journal_enumerate_unique(
+    sd_journal: *mut c_void,
+    field: &str,
+) -> Result<String, JournalError> {
+    let mut data: *mut c_void = std::ptr::null_mut();
+    let mut length: size_t = 0;
+    let ret: libc::c_int;
+
+    unsafe {
+        ret =
+            libsdjournal_bindings::sd_journal_enumerate_unique(sd_journal, &mut data, &mut length);
+    }
+
+    if ret < 0 {
+        return Err(JournalError::Internal(ret));
+    }
+
+    if ret == 0 {
+        return Err(JournalError::EndOfFile);
+    }
+
+    let bytes = unsafe { std::slice::from_raw_parts(data as *const u8, length) };
+    let value = String::from_utf8_lossy(bytes);
+    let prefix = format!("{}=", field);
+
+    match value.strip_prefix(&prefix) {
+        Some(v) => Ok(v.to_owned()),
+        None => Ok(value.into_owned()),
+    }
+}
+
+pub fn sd_journal_restart_unique(sd_journal: *mut c_void) {
+    unsafe {
+        libsdjournal_bindings::sd_journal_restart_unique(sd_journal);
+    }
+}
+
+/// Returns the next field name used by any entry in the journal
+pub fn sd_journal_enumerate_fields(sd_journal: *mut c_void) -> Result<String, JournalError> {
+    let mut field: *const c_char = std::ptr::null();
+    let ret: libc::c_int;
+
+    unsafe {
+        ret = libsdjournal_bindings::sd_journal_enumerate_fields(sd_journal, &mut field);
+    }
+
+    if ret < 0 {
+        return Err(JournalError::Internal(ret));
+    }
+
+    if ret == 0 {
+        return Err(JournalError::EndOfFile);
+    }
+
+    let result = unsafe { CStr::from_ptr(field).to_str().map(String::from) };
+
+    result.map_err(|_| JournalError::Internal(-1))
+}
+
+pub fn sd_journal_restart_fields(sd_journal: *mut c_void) {
+    unsafe {
+        libsdjournal_bindings::sd_journal_restart_fields(sd_journal);
+    }
+}
+
+/// Bytes on disk used by the journal files that are open
+pub fn sd_journal_get_usage(sd_journal: *mut c_void) -> Result<u64, JournalError> {
+    let mut bytes: u64 = 0;
+    let ret: libc::c_int;
+
+    unsafe {
+        ret = libsdjournal_bindings::sd_journal_get_usage(sd_journal, &mut bytes);
+    }
+
+    if ret < 0 {
+        return Err(JournalError::Internal(ret));
+    }
+
+    Ok(bytes)
+}
+
+/// Explanation of the current entry from the message catalog, with the `@FIELD@`
+/// placeholders replaced by the values of the entry. None if its message id has none.
+pub fn sd_journal_get_catalog(sd_journal: *mut c_void) -> Result<Option<String>, JournalError> {
+    let mut text: *mut c_char = std::ptr::null_mut();
+    let ret: libc::c_int;
+
+    unsafe {
+        ret = libsdjournal_bindings::sd_journal_get_catalog(sd_journal, &mut text);
+    }
+
+    take_catalog_text(ret, text)
+}
+
+fn take_catalog_text(ret: libc::c_int, text: *mut c_char) -> Result<Option<String>, JournalError> {
+    // No MESSAGE_ID in the entry, or no catalog entry for it
+    if ret == -libc::ENOENT {
+        return Ok(None);
+    }
+
+    if ret < 0 {
+        return Err(JournalError::Internal(ret));
+    }
+
+    // The text is allocated by libsystemd and has to be freed by the caller
+    let result = unsafe {
+        let value = CStr::from_ptr(text).to_string_lossy().into_owned();
+        libc::free(text as *mut c_void);
+        value
+    };
+
+    Ok(Some(result))
+}
+
+impl WakeupEvent {
+    fn from_code(code: libc::c_int) -> WakeupEvent {
+        match code {
+            1 => WakeupEvent::Append,
+            2 => WakeupEvent::Invalidate,
+            _ => WakeupEvent::Nop,
+        }
+    }
+}
+
+pub fn sd_journal_get_fd(sd_journal: *mut c_void) -> Result<i32, JournalError> {
+    let ret: libc::c_int;
+
+    unsafe {
+        ret = libsdjournal_bindings::sd_journal_get_fd(sd_journal);
+    }
+
+    if ret < 0 {
+        return Err(JournalError::Internal(ret));
+    }
+
+    Ok(ret)
+}
+
+pub fn sd_journal_process(sd_journal: *mut c_void) -> Result<WakeupEvent, JournalError> {
+    let ret: libc::c_int;
+
+    unsafe {
+        ret = libsdjournal_bindings::sd_journal_process(sd_journal);
+    }
+
+    if ret < 0 {
+        return Err(JournalError::Internal(ret));
+    }
+
+    Ok(WakeupEvent::from_code(ret))
+}
+
+pub fn sd_journal_wait(
+    sd_journal: *mut c_void,
+    timeout_usec: u64,
+) -> Result<WakeupEvent, JournalError> {
+    let ret: libc::c_int;
+
+    unsafe {
+        ret = libsdjournal_bindings::sd_journal_wait(sd_journal, timeout_usec);
+    }
+
+    if ret < 0 {
+        return Err(JournalError::Internal(ret));
+    }
+
+    Ok(WakeupEvent::from_code(ret))
+}
+
+pub fn sd_journal_restart_data(sd_journal: *mut c_void) {
+    unsafe {
+        libsdjournal_bindings::sd_journal_restart_data(sd_journal);
+    }
+}
+
+/// Returns the next `FIELD=value` pair of the current entry, keeping the value as raw bytes
+/// for binary fields and values that are not valid UTF-8. Fields that can't be read are skipped.
 pub fn sd_journal_enumerate_data(
     sd_journal: *mut c_void,
-) -> Result<(String, String), JournalError> {
-    // TODO: Refactor so that it returns the whole record not field by field
+) -> Result<Option<(String, Vec<u8>)>, JournalError> {
     let mut data: *mut c_void = std::ptr::null_mut();
     let mut length: size_t = 0;
     let ret: libc::c_int;
@@ -250,7 +573,7 @@ pub fn sd_journal_enumerate_data(
 
     // Skip field in this situation
     if ret == -libc::E2BIG || ret == -libc::ENOBUFS || ret == -libc::EPROTONOSUPPORT {
-        return Ok(("".to_owned(), "".to_owned()));
+        return Ok(None);
     }
 
     if ret < 0 {
@@ -261,22 +584,15 @@ pub fn sd_journal_enumerate_data(
         return Err(JournalError::EndOfFile);
     }
 
-    let result = unsafe {
-        match CStr::from_ptr(data as *mut c_char).to_str() {
-            Ok(s) => {
-                let s = String::from(s);
-                let values = s.split_once('=').unwrap();
-                return Ok((values.0.to_owned(), values.1.to_owned()));
-            }
-            Err(_) => Err(-1),
-        }
-    };
+    let bytes = unsafe { std::slice::from_raw_parts(data as *const u8, length) };
 
-    if let Err(e) = result {
-        return Err(JournalError::Internal(e));
+    match bytes.iter().position(|b| *b == b'=') {
+        Some(i) => Ok(Some((
+            String::from_utf8_lossy(&bytes[..i]).into_owned(),
+            bytes[i + 1..].to_vec(),
+        ))),
+        None => Ok(None),
     }
-
-    Ok(result.unwrap())
 }
 
 // pub fn sd_journal_enumerate_available_data(
@@ -1,10 +1,38 @@
 use libc::{c_char, c_int, c_ulong, c_void, size_t};
 
+/// 128-bit id like a message or boot id, `sd_id128_t` passed by value
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SdId128 {
+    pub qwords: [u64; 2],
+}
+
 #[link(name = "libsystemd.so.0", kind = "dylib", modifiers = "+verbatim")]
 unsafe extern "C" {
     // int sd_journal_open(sd_journal **ret, int flags);
     pub fn sd_journal_open(sd_journal: &mut *mut c_void, flags: u32) -> c_int;
 
+    // int sd_journal_open_namespace(sd_journal **ret, const char *name_space, int flags);
+    pub fn sd_journal_open_namespace(
+        sd_journal: &mut *mut c_void,
+        namespace: *const c_char,
+        flags: u32,
+    ) -> c_int;
+
+    // int sd_journal_open_directory(sd_journal **ret, const char *path, int flags);
+    pub fn sd_journal_open_directory(
+        sd_journal: &mut *mut c_void,
+        path: *const c_char,
+        flags: u32,
+    ) -> c_int;
+
+    // int sd_journal_open_files(sd_journal **ret, const char **paths, int flags);
+    pub fn sd_journal_open_files(
+        sd_journal: &mut *mut c_void,
+        paths: *const *const c_char,
+        flags: u32,
+    ) -> c_int;
+
     //void sd_journal_close(sd_journal *j);
     pub fn sd_journal_close(sd_journal: *mut c_void);
 
@@ -53,6 +81,13 @@ unsafe extern "C" {
     //int sd_journal_get_realtime_usec(sd_journal *j, uint64_t *usec);
     pub fn sd_journal_get_realtime_usec(sd_journal: *mut c_void, microseconds: *mut u64) -> c_int;
 
+    //int sd_journal_get_monotonic_usec(sd_journal *j, uint64_t *usec, sd_id128_t *ret_boot_id);
+    pub fn sd_journal_get_monotonic_usec(
+        sd_journal: *mut c_void,
+        microseconds: *mut u64,
+        boot_id: *mut SdId128,
+    ) -> c_int;
+
     //int sd_journal_seek_realtime_usec(sd_journal *j, uint64_t usec);
     pub fn sd_journal_seek_realtime_usec(sd_journal: *mut c_void, microseconds: u64) -> c_int;
 
@@ -63,6 +98,53 @@ unsafe extern "C" {
         size: *mut size_t,
     ) -> c_int;
 
+    //void sd_journal_restart_data(sd_journal *j);
+    pub fn sd_journal_restart_data(sd_journal: *mut c_void);
+
+    //int sd_journal_get_cursor(sd_journal *j, char **cursor);
+    pub fn sd_journal_get_cursor(sd_journal: *mut c_void, cursor: *mut *mut c_char) -> c_int;
+
+    //int sd_journal_seek_cursor(sd_journal *j, const char *cursor);
+    pub fn sd_journal_seek_cursor(sd_journal: *mut c_void, cursor: *const c_char) -> c_int;
+
+    //int sd_journal_test_cursor(sd_journal *j, const char *cursor);
+    pub fn sd_journal_test_cursor(sd_journal: *mut c_void, cursor: *const c_char) -> c_int;
+
+    //int sd_journal_query_unique(sd_journal *j, const char *field);
+    pub fn sd_journal_query_unique(sd_journal: *mut c_void, field: *const c_char) -> c_int;
+
+    //int sd_journal_enumerate_unique(sd_journal *j, const void **data, size_t *length);
+    pub fn sd_journal_enumerate_unique(
+        sd_journal: *mut c_void,
+        data: &mut *mut c_void,
+        size: *mut size_t,
+    ) -> c_int;
+
+    //void sd_journal_restart_unique(sd_journal *j);
+    pub fn sd_journal_restart_unique(sd_journal: *mut c_void);
+
+    //int sd_journal_enumerate_fields(sd_journal *j, const char **field);
+    pub fn sd_journal_enumerate_fields(sd_journal: *mut c_void, field: &mut *const c_char)
+    -> c_int;
+
+    //void sd_journal_restart_fields(sd_journal *j);
+    pub fn sd_journal_restart_fields(sd_journal: *mut c_void);
+
+    //int sd_journal_get_catalog(sd_journal *j, char **text);
+    pub fn sd_journal_get_catalog(sd_journal: *mut c_void, text: *mut *mut c_char) -> c_int;
+
+    //int sd_journal_get_usage(sd_journal *j, uint64_t *bytes);
+    pub fn sd_journal_get_usage(sd_journal: *mut c_void, bytes: *mut u64) -> c_int;
+
+    //int sd_journal_get_fd(sd_journal *j);
+    pub fn sd_journal_get_fd(sd_journal: *mut c_void) -> c_int;
+
+    //int sd_journal_process(sd_journal *j);
+    pub fn sd_journal_process(sd_journal: *mut c_void) -> c_int;
+
+    //int sd_journal_wait(sd_journal *j, uint64_t timeout_usec);
+    pub fn sd_journal_wait(sd_journal: *mut c_void, timeout_usec: u64) -> c_int;
+
     // TODO: Add support when debian12 is released
     // int sd_journal_enumerate_available_data(sd_journal *j, const void **data, size_t *length);
     // pub fn sd_journal_enumerate_available_data(
@@ -0,0 +1,352 @@
+use super::backend::JournalBackend;
+use super::catalog;
+use super::error::JournalError;
+use super::import::ImportedEntry;
+use super::journal_fields;
+use std::cell::RefCell;
+
+/// Journal kept in memory, with the same matching and positioning rules as libsystemd.
+/// Used for imported dumps and to test the query engine against fixture entries.
+#[derive(Debug, Default)]
+pub struct MemoryJournal {
+    /// Sorted from the oldest to the newest entry
+    entries: Vec<ImportedEntry>,
+    state: RefCell<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    matches: Matches,
+    position: Position,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+enum Position {
+    #[default]
+    Head,
+    Tail,
+    Realtime(u64),
+    /// Seeked to the entry, which is returned by both next and previous
+    Cursor(usize),
+    /// Current entry, after a successful next or previous
+    Entry(usize),
+}
+
+/// Matches in the three levels of libsystemd: an AND of ORs of ANDs of matches,
+/// where the innermost matches of the same field are ORed
+#[derive(Debug, Default)]
+struct Matches {
+    terms: Vec<Vec<Vec<String>>>,
+    disjunction_open: bool,
+    conjunction_open: bool,
+}
+
+impl Matches {
+    fn add(&mut self, m: &str) {
+        if !self.disjunction_open {
+            self.terms.push(vec![]);
+            self.disjunction_open = true;
+        }
+
+        let disjunction = self.terms.last_mut().unwrap();
+        if !self.conjunction_open {
+            disjunction.push(vec![]);
+            self.conjunction_open = true;
+        }
+
+        disjunction.last_mut().unwrap().push(m.to_owned());
+    }
+
+    fn matches(&self, entry: &ImportedEntry) -> bool {
+        self.terms.iter().all(|disjunction| {
+            disjunction.iter().any(|conjunction| {
+                conjunction.iter().all(|m| {
+                    let (field, _) = m.split_once('=').unwrap_or((m, ""));
+
+                    // Any of the matches of the same field
+                    conjunction
+                        .iter()
+                        .filter(|other| other.split_once('=').map(|(f, _)| f) == Some(field))
+                        .any(|other| {
+                            let (_, value) = other.split_once('=').unwrap();
+                            entry.has(field, value)
+                        })
+                })
+            })
+        })
+    }
+}
+
+impl MemoryJournal {
+    pub fn new(mut entries: Vec<ImportedEntry>) -> MemoryJournal {
+        entries.sort_by_key(|e| e.realtime);
+
+        MemoryJournal {
+            entries,
+            state: RefCell::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn current(&self) -> Result<&ImportedEntry, JournalError> {
+        match self.state.borrow().position {
+            Position::Entry(i) => Ok(&self.entries[i]),
+            _ => Err(JournalError::Internal(-libc::EADDRNOTAVAIL)),
+        }
+    }
+
+    fn matches(&self, i: usize) -> bool {
+        self.state.borrow().matches.matches(&self.entries[i])
+    }
+
+    fn move_to(&self, found: Option<usize>) -> bool {
+        match found {
+            Some(i) => {
+                self.state.borrow_mut().position = Position::Entry(i);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl JournalBackend for MemoryJournal {
+    fn add_match(&self, m: &str) -> Result<(), JournalError> {
+        if !m.contains('=') {
+            return Err(JournalError::Internal(-libc::EINVAL));
+        }
+
+        self.state.borrow_mut().matches.add(m);
+        Ok(())
+    }
+
+    fn add_disjunction(&self) -> Result<(), JournalError> {
+        self.state.borrow_mut().matches.conjunction_open = false;
+        Ok(())
+    }
+
+    fn add_conjunction(&self) -> Result<(), JournalError> {
+        let matches = &mut self.state.borrow_mut().matches;
+        matches.disjunction_open = false;
+        matches.conjunction_open = false;
+        Ok(())
+    }
+
+    fn flush_matches(&self) {
+        self.state.borrow_mut().matches = Matches::default();
+    }
+
+    fn seek_head(&self) -> Result<(), JournalError> {
+        self.state.borrow_mut().position = Position::Head;
+        Ok(())
+    }
+
+    fn seek_tail(&self) -> Result<(), JournalError> {
+        self.state.borrow_mut().position = Position::Tail;
+        Ok(())
+    }
+
+    fn seek_realtime_usec(&self, microseconds: u64) -> Result<(), JournalError> {
+        self.state.borrow_mut().position = Position::Realtime(microseconds);
+        Ok(())
+    }
+
+    fn seek_cursor(&self, cursor: &str) -> Result<(), JournalError> {
+        let i = self
+            .entries
+            .iter()
+            .position(|e| e.cursor == cursor)
+            .ok_or(JournalError::NotFound)?;
+
+        self.state.borrow_mut().position = Position::Cursor(i);
+        Ok(())
+    }
+
+    fn test_cursor(&self, cursor: &str) -> Result<bool, JournalError> {
+        Ok(self.current()?.cursor == cursor)
+    }
+
+    fn next(&self) -> Result<bool, JournalError> {
+        let start = match self.state.borrow().position {
+            Position::Head => 0,
+            Position::Tail => self.entries.len(),
+            Position::Realtime(t) => self.entries.partition_point(|e| e.realtime < t),
+            Position::Cursor(i) => i,
+            Position::Entry(i) => i + 1,
+        };
+
+        Ok(self.move_to((start..self.entries.len()).find(|i| self.matches(*i))))
+    }
+
+    fn previous(&self) -> Result<bool, JournalError> {
+        let end = match self.state.borrow().position {
+            Position::Head => 0,
+            Position::Tail => self.entries.len(),
+            Position::Realtime(t) => self.entries.partition_point(|e| e.realtime <= t),
+            Position::Cursor(i) => i + 1,
+            Position::Entry(i) => i,
+        };
+
+        Ok(self.move_to((0..end).rev().find(|i| self.matches(*i))))
+    }
+
+    fn get_realtime_usec(&self) -> Result<u64, JournalError> {
+        Ok(self.current()?.realtime)
+    }
+
+    fn get_monotonic_usec(&self) -> Result<u64, JournalError> {
+        match self.current()?.monotonic {
+            0 => Err(JournalError::Internal(-libc::ENODATA)),
+            monotonic => Ok(monotonic),
+        }
+    }
+
+    fn get_cursor(&self) -> Result<String, JournalError> {
+        Ok(self.current()?.cursor.clone())
+    }
+
+    fn get_data(&self, field: &str) -> Result<String, JournalError> {
+        self.current()?
+            .get(field)
+            .map(str::to_owned)
+            .ok_or(JournalError::Internal(-libc::ENOENT))
+    }
+
+    fn enumerate_data(&self) -> Result<Vec<(String, Vec<u8>)>, JournalError> {
+        Ok(self
+            .current()?
+            .fields
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone().into_bytes()))
+            .collect())
+    }
+
+    /// Looked up in the catalog of the local machine, which also explains imported entries
+    fn get_catalog(&self) -> Result<Option<String>, JournalError> {
+        let entry = self.current()?;
+        let Some(message_id) = entry.get(journal_fields::MESSAGE_ID) else {
+            return Ok(None);
+        };
+
+        let text = catalog::get_catalog_text("/", message_id)?;
+
+        Ok(text.map(|t| catalog::replace_fields(&t, |field| entry.get(field).map(str::to_owned))))
+    }
+
+    fn enumerate_fields(&self) -> Result<Vec<String>, JournalError> {
+        let mut names: Vec<String> = vec![];
+
+        for (name, _) in self.entries.iter().flat_map(|e| e.fields.iter()) {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+
+        Ok(names)
+    }
+
+    fn query_unique(&self, field: &str) -> Result<Vec<String>, JournalError> {
+        let mut values: Vec<String> = vec![];
+
+        for (_, value) in self
+            .entries
+            .iter()
+            .flat_map(|e| e.fields.iter())
+            .filter(|(name, _)| name == field)
+        {
+            if !values.contains(value) {
+                values.push(value.clone());
+            }
+        }
+
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemoryJournal;
+    use crate::journal::backend::JournalBackend;
+    use crate::journal::testing::entry;
+
+    fn journal() -> MemoryJournal {
+        MemoryJournal::new(vec![
+            entry(3, &[("_COMM", "sshd"), ("PRIORITY", "3")]),
+            entry(1, &[("_COMM", "bash"), ("PRIORITY", "6")]),
+            entry(2, &[("_COMM", "sshd"), ("PRIORITY", "6")]),
+            entry(4, &[("_COMM", "cron"), ("PRIORITY", "3")]),
+        ])
+    }
+
+    fn forward(j: &MemoryJournal) -> Vec<u64> {
+        let mut found = vec![];
+        j.seek_head().unwrap();
+        while j.next().unwrap() {
+            found.push(j.get_realtime_usec().unwrap());
+        }
+        found
+    }
+
+    #[test]
+    fn same_field_ored_different_fields_anded() {
+        let j = journal();
+        j.add_match("_COMM=sshd").unwrap();
+        j.add_match("_COMM=cron").unwrap();
+        assert_eq!(forward(&j), vec![2, 3, 4]);
+
+        j.add_match("PRIORITY=3").unwrap();
+        assert_eq!(forward(&j), vec![3, 4]);
+    }
+
+    #[test]
+    fn disjunctions_and_conjunctions() {
+        let j = journal();
+        // (_COMM=bash) OR (_COMM=cron AND PRIORITY=3)
+        j.add_match("_COMM=bash").unwrap();
+        j.add_disjunction().unwrap();
+        j.add_match("_COMM=cron").unwrap();
+        j.add_match("PRIORITY=3").unwrap();
+        assert_eq!(forward(&j), vec![1, 4]);
+
+        // ... AND (PRIORITY=6)
+        j.add_conjunction().unwrap();
+        j.add_match("PRIORITY=6").unwrap();
+        assert_eq!(forward(&j), vec![1]);
+
+        j.flush_matches();
+        assert_eq!(forward(&j), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn seeking() {
+        let j = journal();
+
+        j.seek_tail().unwrap();
+        assert!(!j.next().unwrap());
+        assert!(j.previous().unwrap());
+        assert_eq!(j.get_realtime_usec().unwrap(), 4);
+
+        j.seek_realtime_usec(2).unwrap();
+        assert!(j.previous().unwrap());
+        assert_eq!(j.get_realtime_usec().unwrap(), 2);
+        assert!(j.previous().unwrap());
+        assert_eq!(j.get_realtime_usec().unwrap(), 1);
+        assert!(!j.previous().unwrap());
+
+        j.seek_cursor("c3").unwrap();
+        assert!(j.next().unwrap());
+        assert!(j.test_cursor("c3").unwrap());
+        assert_eq!(j.get_data("_COMM").unwrap(), "sshd");
+        assert!(j.get_data("MESSAGE").is_err());
+
+        j.seek_head().unwrap();
+        assert!(j.get_realtime_usec().is_err());
+    }
+}
@@ -1,26 +1,80 @@
+mod audit;
+mod backend;
 mod boot;
+mod catalog;
+mod coredump;
+mod error;
+mod exclusions;
+mod export;
+mod field_info;
+mod filter_expression;
+mod follow;
+mod glob;
+mod histogram;
+mod import;
 mod journal_entries;
 mod journal_fields;
+pub mod journal_file;
+mod journal_source;
+mod journald_conf;
+mod kernel;
+#[cfg(feature = "libsystemd")]
 mod libsdjournal;
+#[cfg(feature = "libsystemd")]
 mod libsdjournal_bindings;
+mod memory_journal;
+mod namespace;
+mod page;
 mod query;
 mod query_builder;
+mod search;
+mod sessions;
+mod storage;
+mod stream;
+#[cfg(test)]
+pub(crate) mod testing;
 mod unit;
 
+pub use audit::AuditEvent;
+#[cfg(feature = "libsystemd")]
+pub use backend::SdJournal;
+use backend::WakeupEvent;
+pub use backend::{JournalBackend, SourceBackend};
+#[cfg(feature = "libsystemd")]
 use bitflags::bitflags;
 pub use boot::Boot;
+pub use catalog::CatalogEntry;
+pub use coredump::Coredump;
+pub use error::JournalError;
+pub use exclusions::Exclusions;
+pub use export::{EntryWriter, ExportFormat};
+pub use field_info::FieldInfo;
+pub use filter_expression::FilterExpression;
+pub use follow::Follower;
+pub use histogram::Histogram;
+#[cfg(test)]
+pub use import::ImportedEntry;
 pub use journal_entries::JournalEntries;
 pub use journal_entries::JournalEntry;
 use journal_fields::MESSAGE;
 use journal_fields::SOURCE_REALTIME_TIMESTAMP;
-use libc::c_void;
-pub use libsdjournal::JournalError;
-use libsdjournal::*;
-use query::Query;
+pub use journal_source::JournalSource;
+pub use kernel::KernelFilter;
+pub use memory_journal::MemoryJournal;
+use page::PageRows;
+pub use page::{Anchor, JournalPage};
+pub use query::Query;
 pub use query_builder::QueryBuilder;
-use std::process::Command;
-pub use unit::Unit;
+pub use search::{Search, SearchMode};
+pub use sessions::{SessionId, Sessions};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+pub use storage::StorageReport;
+use stream::Scanned;
+pub use stream::{QueryEvent, QueryProgress};
+pub use unit::{Unit, UnitType};
 
+#[cfg(feature = "libsystemd")]
 bitflags! {
     #[repr(C)]
     pub struct OpenFlags: u32 {
@@ -32,28 +86,226 @@ bitflags! {
         const SD_JOURNAL_SYSTEM = 1 << 2;
         /// Current user
         const SD_JOURNAL_CURRENT_USER = 1 << 3;
+        /// Every namespace, the given one is ignored
+        const SD_JOURNAL_ALL_NAMESPACES = 1 << 5;
+        /// The default namespace along with the given one
+        const SD_JOURNAL_INCLUDE_DEFAULT_NAMESPACE = 1 << 6;
     }
 }
 
 pub const INIT_UNIT: &str = "Init (Systemd)";
 
+/// How long to block waiting for journal changes before checking if following was stopped
+const FOLLOW_WAIT_USEC: u64 = 250_000;
+
+/// Number of rows sent at once when streaming a query
+const STREAM_CHUNK_SIZE: usize = 200;
+
+/// Number of entries read between two progress reports of a streamed query
+const PROGRESS_INTERVAL: u64 = 10_000;
+
+/// Query engine over a journal, opened on a source unless another backend is given
 #[derive(Debug)]
-pub struct Journal {
-    ptr: *mut c_void,
+pub struct Journal<B: JournalBackend = SourceBackend> {
+    backend: B,
 }
 
 impl Journal {
-    fn new() -> Journal {
-        Journal {
-            ptr: std::ptr::null_mut(),
+    #[cfg(feature = "libsystemd")]
+    pub fn open(open_flags: OpenFlags) -> Result<Journal, JournalError> {
+        Ok(Journal::with_sd(SdJournal::open(open_flags.bits())?))
+    }
+
+    /// Opens a journal namespace of the running system, like `journalctl --namespace`
+    #[cfg(feature = "libsystemd")]
+    pub fn open_namespace(namespace: &str) -> Result<Journal, JournalError> {
+        let flags = OpenFlags::SD_JOURNAL_LOCAL_ONLY
+            | OpenFlags::SD_JOURNAL_SYSTEM
+            | OpenFlags::SD_JOURNAL_CURRENT_USER;
+
+        let backend = if namespace == "*" {
+            SdJournal::open_namespace(None, (flags | OpenFlags::SD_JOURNAL_ALL_NAMESPACES).bits())?
+        } else if let Some(namespace) = namespace.strip_prefix('+') {
+            SdJournal::open_namespace(
+                Some(namespace),
+                (flags | OpenFlags::SD_JOURNAL_INCLUDE_DEFAULT_NAMESPACE).bits(),
+            )?
+        } else {
+            SdJournal::open_namespace(Some(namespace), flags.bits())?
+        };
+
+        Ok(Journal::with_sd(backend))
+    }
+
+    /// Opens all journal files found in a directory, like `journalctl --directory`
+    #[cfg(feature = "libsystemd")]
+    pub fn open_directory(path: &str) -> Result<Journal, JournalError> {
+        Ok(Journal::with_sd(SdJournal::open_directory(path)?))
+    }
+
+    /// Opens the given journal files, like `journalctl --file`
+    #[cfg(feature = "libsystemd")]
+    pub fn open_files(paths: &[String]) -> Result<Journal, JournalError> {
+        Ok(Journal::with_sd(SdJournal::open_files(paths)?))
+    }
+
+    /// Reads a `journalctl -o export` or `-o json` dump into memory, the whole file is parsed
+    pub fn open_import(path: &str) -> Result<Journal, JournalError> {
+        let entries = import::read_file(path)?;
+        debug!("Imported {} entries from {}", entries.len(), path);
+
+        Ok(Journal::with_backend(SourceBackend::Memory(
+            MemoryJournal::new(entries),
+        )))
+    }
+
+    #[cfg(feature = "libsystemd")]
+    pub fn open_source(source: &JournalSource) -> Result<Journal, JournalError> {
+        match source {
+            JournalSource::Local => Journal::open(
+                OpenFlags::SD_JOURNAL_LOCAL_ONLY
+                    | OpenFlags::SD_JOURNAL_SYSTEM
+                    | OpenFlags::SD_JOURNAL_CURRENT_USER,
+            ),
+            JournalSource::Namespace { namespace } => Journal::open_namespace(namespace),
+            JournalSource::Directory { path } => Journal::open_directory(path),
+            JournalSource::Files { paths } => Journal::open_files(paths),
+            JournalSource::Import { path } => Journal::open_import(path),
         }
     }
 
-    pub fn open(open_flags: OpenFlags) -> Result<Journal, JournalError> {
-        let mut journal = Journal::new();
-        sd_journal_open(&mut journal.ptr, open_flags.bits())?;
+    /// Without libsystemd the journal files of the source are read into memory, all of
+    /// them at once
+    #[cfg(not(feature = "libsystemd"))]
+    pub fn open_source(source: &JournalSource) -> Result<Journal, JournalError> {
+        match source {
+            JournalSource::Import { path } => Journal::open_import(path),
+            source => Journal::read_journal_files(source),
+        }
+    }
+
+    /// Reads the journal files of the source with the journal file reader, the ones
+    /// that can't be read are skipped like libsystemd does
+    #[cfg(all(not(feature = "libsystemd"), feature = "journal-file"))]
+    fn read_journal_files(source: &JournalSource) -> Result<Journal, JournalError> {
+        let mut entries = vec![];
+
+        for file in storage::list_journal_files(source) {
+            match journal_file::JournalFile::open(&file.path).and_then(|f| f.entries()) {
+                Ok(read) => entries.extend(read.into_iter().map(import::ImportedEntry::from)),
+                Err(e) => warn!("Could not read the journal file {}: {}", file.path, e),
+            }
+        }
+        debug!("Read {} entries from the journal files", entries.len());
+
+        Ok(Journal::with_backend(SourceBackend::Memory(
+            MemoryJournal::new(entries),
+        )))
+    }
+
+    #[cfg(all(not(feature = "libsystemd"), not(feature = "journal-file")))]
+    fn read_journal_files(_source: &JournalSource) -> Result<Journal, JournalError> {
+        Err(JournalError::InvalidFile(
+            "reading journal files needs libsystemd or the journal-file feature".into(),
+        ))
+    }
+
+    #[cfg(feature = "libsystemd")]
+    fn with_sd(backend: SdJournal) -> Journal {
+        Journal::with_backend(SourceBackend::Sd(backend))
+    }
+
+    /// Waits for new entries matching the query and hands them over to `on_entries`
+    /// in batches, until `stop` is set. Only entries written after the call are reported.
+    pub fn follow<F>(
+        &self,
+        q: &Query,
+        stop: &AtomicBool,
+        mut on_entries: F,
+    ) -> Result<(), JournalError>
+    where
+        F: FnMut(JournalEntries),
+    {
+        self.apply_filters(q)?;
+
+        // Allocate the inotify descriptor before positioning, so no entry is missed
+        self.backend.get_fd()?;
+        self.backend.seek_tail()?;
+        // Step back onto the newest entry, sd_journal_next will return what comes after it
+        self.backend.previous()?;
+
+        while !stop.load(Ordering::Relaxed) {
+            let mut journal_entries = JournalEntries::new(0);
+            journal_entries.headers.extend(q.fields.iter().cloned());
+
+            while self.backend.next()? {
+                if !self.matches_quick_search(q) || !self.matches_post_filters(q) {
+                    continue;
+                }
+
+                journal_entries.rows.push(self.read_row(q));
+            }
+
+            if !journal_entries.rows.is_empty() {
+                debug!("Following {} new entries", journal_entries.rows.len());
+                on_entries(journal_entries);
+            }
+
+            if self.backend.wait(FOLLOW_WAIT_USEC)? == WakeupEvent::Invalidate {
+                debug!("Journal files changed while following");
+            }
+        }
+
+        debug!("Stopped following the journal");
+        Ok(())
+    }
 
-        Ok(journal)
+    /// Namespaces of the running system with journal files, besides the default one
+    pub fn list_namespaces() -> Vec<String> {
+        namespace::list_namespaces()
+    }
+
+    /// Message ids explained in the catalog, with their subjects
+    pub fn list_catalog() -> Vec<CatalogEntry> {
+        catalog::list_catalog("/")
+    }
+
+    /// Disk usage of the journal with its files and, for the default namespace of
+    /// the running system, the limits journald is configured with
+    pub fn storage_report(source: &JournalSource) -> Result<StorageReport, JournalError> {
+        let files = storage::list_journal_files(source);
+        let usage = match source {
+            // Read into memory, the dump is the only file
+            JournalSource::Import { path } => std::fs::metadata(path)?.len(),
+            #[cfg(feature = "libsystemd")]
+            _ => Journal::open_source(source)?.backend.get_usage()?,
+            #[cfg(not(feature = "libsystemd"))]
+            _ => files.iter().map(|f| f.size).sum(),
+        };
+
+        Ok(StorageReport {
+            usage,
+            files,
+            // journald.conf only configures the default namespace
+            config: (*source == JournalSource::Local)
+                .then(|| journald_conf::read_journald_conf("/")),
+        })
+    }
+
+    /// Catalog text of a message id with the `@FIELD@` placeholders left as they are, there
+    /// is no entry to take their values from. None if the id is not in the catalog.
+    pub fn get_catalog_text(message_id: &str) -> Result<Option<String>, JournalError> {
+        catalog::get_catalog_text("/", message_id)
+    }
+}
+
+impl<B: JournalBackend> Journal<B> {
+    pub fn with_backend(backend: B) -> Journal<B> {
+        Journal { backend }
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
     }
 
     pub fn get_logs(&self) -> Result<JournalEntries, JournalError> {
@@ -67,140 +319,543 @@ impl Journal {
     }
 
     fn get_logs_internal(&self, q: &Query) -> Result<JournalEntries, JournalError> {
-        sd_journal_flush_matches(self.ptr);
-
-        self.apply_pid_filter(q);
-        self.apply_minimum_priority(q);
-        self.apply_units(q);
-        self.apply_slice(q);
-        self.apply_boot_ids(q);
-        self.apply_transports_filter(q);
-
         let mut journal_entries = JournalEntries::new(q.limit as usize);
 
         for field in q.fields.iter() {
             journal_entries.headers.push((*field).to_string())
         }
 
-        if q.reset_position {
-            sd_journal_seek_tail(self.ptr)?;
+        self.scan_logs(q, &AtomicBool::new(false), |scanned| {
+            if let Scanned::Row(row) = scanned {
+                journal_entries.rows.push(row);
+            }
+        })?;
+
+        Ok(journal_entries)
+    }
+
+    /// Runs the query like `query_logs`, handing the rows over in chunks along with the
+    /// progress of the scan, until it is done or `cancel` is set. The last event is always
+    /// `QueryEvent::Finished`.
+    pub fn stream_logs<F>(
+        &self,
+        q: &Query,
+        cancel: &AtomicBool,
+        mut on_event: F,
+    ) -> Result<(), JournalError>
+    where
+        F: FnMut(QueryEvent),
+    {
+        let new_chunk = || {
+            let mut chunk = JournalEntries::new(STREAM_CHUNK_SIZE);
+            chunk.headers.extend(q.fields.iter().cloned());
+            chunk
+        };
+        let mut chunk = new_chunk();
+
+        let progress = self.scan_logs(q, cancel, |scanned| match scanned {
+            Scanned::Row(row) => {
+                chunk.rows.push(row);
+
+                if chunk.rows.len() >= STREAM_CHUNK_SIZE {
+                    on_event(QueryEvent::Rows(std::mem::replace(&mut chunk, new_chunk())));
+                }
+            }
+            Scanned::Progress(progress) => {
+                // Rows of a narrow query shouldn't wait for a full chunk
+                if !chunk.rows.is_empty() {
+                    on_event(QueryEvent::Rows(std::mem::replace(&mut chunk, new_chunk())));
+                }
+
+                on_event(QueryEvent::Progress(progress.clone()));
+            }
+        })?;
+
+        if !chunk.rows.is_empty() {
+            on_event(QueryEvent::Rows(chunk));
         }
 
-        if q.reset_position && q.date_less_than > 0 {
-            sd_journal_seek_realtime_usec(self.ptr, q.date_less_than)?;
+        on_event(QueryEvent::Finished(progress));
+
+        Ok(())
+    }
+
+    /// Walks the journal from the newest to the oldest entry matching the query, reporting
+    /// the rows and, every `PROGRESS_INTERVAL` entries read, the progress
+    fn scan_logs<F>(
+        &self,
+        q: &Query,
+        cancel: &AtomicBool,
+        mut on_scanned: F,
+    ) -> Result<QueryProgress, JournalError>
+    where
+        F: FnMut(Scanned),
+    {
+        self.apply_filters(q)?;
+
+        // Continue from the cursor of the last returned entry, which is excluded
+        let mut skip_cursor_entry = !q.cursor.is_empty();
+
+        if skip_cursor_entry {
+            self.backend.seek_cursor(&q.cursor)?;
+        } else if q.reset_position {
+            self.backend.seek_tail()?;
+
+            if q.date_less_than > 0 {
+                self.backend.seek_realtime_usec(q.date_less_than)?;
+            }
         }
 
-        let mut count: u64 = 0;
-        let mut last_timestamp: u64 = 0;
+        let mut progress = QueryProgress::default();
 
         loop {
-            let more = sd_journal_previous(self.ptr)?;
+            if cancel.load(Ordering::Relaxed) {
+                debug!("Query cancelled after {} entries", progress.scanned);
+                progress.cancelled = true;
+                break;
+            }
+
+            let more = self.backend.previous()?;
 
             if !more {
                 debug!("No more entries");
                 break;
             }
 
-            if let Ok(updated_timestamp) = self.get_field(SOURCE_REALTIME_TIMESTAMP) {
-                last_timestamp = updated_timestamp.parse().unwrap();
-                trace!(
-                    "Last timestamp {:?}",
-                    chrono::DateTime::from_timestamp_micros(last_timestamp.try_into().unwrap())
-                )
+            if skip_cursor_entry {
+                skip_cursor_entry = false;
+                if self.backend.test_cursor(&q.cursor)? {
+                    continue;
+                }
             }
 
-            if !q.quick_search.is_empty() {
-                if let Ok(message) = self.get_field(MESSAGE) {
-                    if !message.to_lowercase().contains(&q.quick_search) {
-                        continue;
-                    }
-                }
+            let realtime = self.backend.get_realtime_usec()?;
+
+            progress.scanned += 1;
+            progress.timestamp = realtime;
+            if progress.scanned % PROGRESS_INTERVAL == 0 {
+                on_scanned(Scanned::Progress(&progress));
             }
 
-            if q.limit > 0 && count >= q.limit {
-                debug!("Reached limit of {}", q.limit);
-                break;
+            // The upper bound is exclusive, seeking to it lands on the last entry at that time
+            if q.date_less_than > 0 && realtime >= q.date_less_than {
+                continue;
             }
 
+            // Entries logged by the kernel or journald itself have no source timestamp
+            let last_timestamp: u64 = match self.get_field(SOURCE_REALTIME_TIMESTAMP) {
+                Ok(updated_timestamp) => updated_timestamp.parse().unwrap_or(realtime),
+                Err(_) => realtime,
+            };
+            trace!(
+                "Last timestamp {:?}",
+                chrono::DateTime::from_timestamp_micros(last_timestamp.try_into().unwrap())
+            );
+
+            // Check the date before skipping entries, otherwise a search keeps reading past it
             if q.date_more_than > 0 && q.date_more_than >= last_timestamp {
                 debug!("Reached epoch time of {}", q.date_more_than);
                 break;
             }
 
-            let mut row: Vec<String> = Vec::with_capacity(q.fields.len());
-
-            for field in q.fields.iter() {
-                match field.as_str() {
-                    "__REALTIME" => {
-                        let mut realtime: u64 = 0;
-                        match sd_journal_get_realtime_usec(self.ptr, &mut realtime) {
-                            Ok(()) => row.push(realtime.to_string()),
-                            Err(JournalError::Internal(e)) => {
-                                row.push(String::new());
-                                warn!("Could not get realtime field, error: {}", e);
-                            }
-                            Err(JournalError::EndOfFile) => {
-                                panic!("should not return end of file")
-                            }
-                        }
+            if !self.matches_quick_search(q) || !self.matches_post_filters(q) {
+                continue;
+            }
+
+            if q.limit > 0 && progress.matched >= q.limit {
+                debug!("Reached limit of {}", q.limit);
+                break;
+            }
+
+            on_scanned(Scanned::Row(self.read_row(q)));
+            progress.matched += 1;
+        }
+
+        Ok(progress)
+    }
+
+    /// Entries matching the query around an anchor, up to `before` older and `after` newer
+    /// ones, with tokens to continue in either direction. Nothing depends on the position
+    /// left by a previous call.
+    pub fn get_page(
+        &self,
+        q: &Query,
+        anchor: &Anchor,
+        before: u64,
+        after: u64,
+    ) -> Result<JournalPage, JournalError> {
+        self.apply_filters(q)?;
+        self.seek_anchor(anchor)?;
+
+        let headers = q.fields.clone();
+
+        match anchor {
+            Anchor::Older { token } => {
+                let older = self.read_page_rows(q, false, before, Some(token))?;
+                let mut page = JournalPage::new(headers, PageRows::continued(), None, older);
+                page.newer.get_or_insert_with(|| token.clone());
+
+                Ok(page)
+            }
+            Anchor::Newer { token } => {
+                let newer = self.read_page_rows(q, true, after, Some(token))?;
+                let mut page = JournalPage::new(headers, newer, None, PageRows::continued());
+                page.older.get_or_insert_with(|| token.clone());
+
+                Ok(page)
+            }
+            Anchor::Cursor { .. } | Anchor::Realtime { .. } => {
+                // The anchor is the first entry at or after the position, or the last one
+                // before it when there is none after
+                let found = self.step(q, true)? || {
+                    self.seek_anchor(anchor)?;
+                    self.step(q, false)?
+                };
+
+                if !found {
+                    debug!("No entry around the anchor");
+                    return Ok(JournalPage::new(
+                        headers,
+                        PageRows::default(),
+                        None,
+                        PageRows::default(),
+                    ));
+                }
+
+                let cursor = self.backend.get_cursor()?;
+                let row = self.read_row(q);
+
+                self.backend.seek_cursor(&cursor)?;
+                let newer = self.read_page_rows(q, true, after, Some(&cursor))?;
+                self.backend.seek_cursor(&cursor)?;
+                let older = self.read_page_rows(q, false, before, Some(&cursor))?;
+
+                Ok(JournalPage::new(headers, newer, Some((cursor, row)), older))
+            }
+        }
+    }
+
+    fn seek_anchor(&self, anchor: &Anchor) -> Result<(), JournalError> {
+        match anchor {
+            Anchor::Cursor { cursor: c }
+            | Anchor::Older { token: c }
+            | Anchor::Newer { token: c } => self.backend.seek_cursor(c),
+            Anchor::Realtime { realtime } => self.backend.seek_realtime_usec(*realtime),
+        }
+    }
+
+    /// Reads up to `count` entries from the current position towards newer or older ones,
+    /// leaving out the entry of the cursor seeked to, as seeking is inclusive
+    fn read_page_rows(
+        &self,
+        q: &Query,
+        newer: bool,
+        count: u64,
+        seeked: Option<&str>,
+    ) -> Result<PageRows, JournalError> {
+        let mut page_rows = PageRows::default();
+        let mut first = true;
+
+        while self.step(q, newer)? {
+            if first {
+                first = false;
+                if let Some(cursor) = seeked {
+                    if self.backend.test_cursor(cursor)? {
+                        continue;
                     }
-                    _ => match self.get_field(field) {
-                        Ok(data) => {
-                            row.push(data);
-                        }
-                        Err(e) => {
-                            row.push(String::new());
-                            warn!("Could not find the field: {}, JournalError: {}", &field, e);
-                        }
-                    },
                 }
             }
 
-            journal_entries.rows.push(row);
+            if page_rows.rows.len() as u64 >= count {
+                page_rows.more = true;
+                break;
+            }
+
+            page_rows.push(self.backend.get_cursor()?, self.read_row(q));
+        }
+
+        Ok(page_rows)
+    }
+
+    /// Moves to the next or previous entry matching the query, including the date range,
+    /// the quick search and the post filters. Returns false when there is none left.
+    fn step(&self, q: &Query, newer: bool) -> Result<bool, JournalError> {
+        loop {
+            let more = if newer {
+                self.backend.next()?
+            } else {
+                self.backend.previous()?
+            };
+
+            if !more {
+                return Ok(false);
+            }
+
+            let realtime = self.backend.get_realtime_usec()?;
+
+            if q.date_less_than > 0 && realtime >= q.date_less_than {
+                if newer {
+                    return Ok(false);
+                }
+                continue;
+            }
+
+            if q.date_more_than > 0 && realtime <= q.date_more_than {
+                if !newer {
+                    return Ok(false);
+                }
+                continue;
+            }
+
+            if self.matches_quick_search(q) && self.matches_post_filters(q) {
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Counts the entries matching the query between two timestamps in microseconds,
+    /// walking the whole range without any limit
+    pub fn get_histogram(
+        &self,
+        q: &Query,
+        from: u64,
+        to: u64,
+        bucket_width: u64,
+    ) -> Result<Histogram, JournalError> {
+        let mut histogram = Histogram::new(from, to, bucket_width);
+
+        self.apply_filters(q)?;
+        self.backend.seek_realtime_usec(histogram.from)?;
+
+        while self.backend.next()? {
+            let realtime = self.backend.get_realtime_usec()?;
+
+            if realtime >= histogram.to {
+                break;
+            }
+
+            if !self.matches_quick_search(q) || !self.matches_post_filters(q) {
+                continue;
+            }
+
+            let priority = self
+                .get_field(journal_fields::PRIORITY)
+                .ok()
+                .and_then(|p| p.parse::<usize>().ok());
+
+            histogram.add(realtime, priority);
+        }
+
+        Ok(histogram)
+    }
+
+    /// Writes every entry matching the query in chronological order, ignoring the limit.
+    /// Returns the number of exported entries.
+    pub fn export<W: Write>(
+        &self,
+        q: &Query,
+        writer: &mut EntryWriter<W>,
+    ) -> Result<u64, JournalError> {
+        self.apply_filters(q)?;
+
+        if q.date_more_than > 0 {
+            self.backend.seek_realtime_usec(q.date_more_than)?;
+        } else {
+            self.backend.seek_head()?;
+        }
+
+        let mut count: u64 = 0;
+
+        while self.backend.next()? {
+            let realtime = self.backend.get_realtime_usec()?;
+
+            if q.date_less_than > 0 && realtime >= q.date_less_than {
+                break;
+            }
+
+            if !self.matches_quick_search(q) || !self.matches_post_filters(q) {
+                continue;
+            }
+
+            if writer.format().exports_all_fields() {
+                writer.write_entry(&self.read_export_fields(realtime)?)?;
+            } else {
+                let row = self.read_fields(writer.fields());
+                writer.write_row(&row)?;
+            }
+
             count += 1;
         }
 
-        Ok(journal_entries)
+        Ok(count)
     }
 
-    pub fn get_full_entry(&self, timestamp: u64) -> Result<JournalEntry, JournalError> {
-        sd_journal_seek_realtime_usec(self.ptr, timestamp)?;
+    /// Every field of the current entry as raw bytes, preceded by the
+    /// cursor and timestamp, as expected by the Journal Export Format
+    fn read_export_fields(&self, realtime: u64) -> Result<Vec<(String, Vec<u8>)>, JournalError> {
+        let mut fields = vec![
+            (
+                journal_fields::CURSOR.to_owned(),
+                self.backend.get_cursor()?.into_bytes(),
+            ),
+            (
+                journal_fields::REALTIME_TIMESTAMP.to_owned(),
+                realtime.to_string().into_bytes(),
+            ),
+        ];
+        match self.backend.get_monotonic_usec() {
+            Ok(monotonic) => fields.push((
+                journal_fields::MONOTONIC_TIMESTAMP.to_owned(),
+                monotonic.to_string().into_bytes(),
+            )),
+            // Entries imported without it
+            Err(JournalError::Internal(e)) if e == -libc::ENODATA => {}
+            Err(e) => return Err(e),
+        }
+        fields.extend(self.backend.enumerate_data()?);
 
-        let more = sd_journal_previous(self.ptr)?;
+        Ok(fields)
+    }
+
+    fn apply_filters(&self, q: &Query) -> Result<(), JournalError> {
+        // Read before adding the matches, as it looks at the whole journal
+        let current_boot_id = if q.current_boot && q.boot_ids.is_empty() {
+            self.last_boot_id()
+        } else {
+            None
+        };
+
+        self.backend.flush_matches();
+
+        self.apply_pid_filter(q);
+        self.apply_priorities(q);
+        self.apply_units(q)?;
+        self.apply_slice(q);
+        self.apply_boot_ids(q);
+        self.apply_current_boot(current_boot_id);
+        self.apply_namespaces(q);
+        self.apply_transports_filter(q);
+        self.apply_kernel_filter(q);
+        self.apply_filter_expression(q)
+    }
+
+    fn matches_quick_search(&self, q: &Query) -> bool {
+        match &q.search {
+            Some(search) => match self.get_field(search.field()) {
+                Ok(value) => search.is_match(&value),
+                Err(_) => false,
+            },
+            None => true,
+        }
+    }
 
-        if !more {
-            error!("Entry not found by the timestamp");
-            return Err(JournalError::Internal(0));
+    fn matches_post_filters(&self, q: &Query) -> bool {
+        if !q.exclusions.is_empty() && q.exclusions.excludes(|field| self.get_field(field).ok()) {
+            return false;
         }
 
-        let mut entry = JournalEntry::new();
+        if q.default_namespace {
+            let namespace = self.get_field(journal_fields::NAMESPACE).ok();
+            if namespace.is_some_and(|n| !q.namespaces.contains(&n)) {
+                return false;
+            }
+        }
 
-        loop {
-            match sd_journal_enumerate_data(self.ptr) {
-                Ok(x) => {
-                    if !x.0.is_empty() {
-                        entry.headers.push(x.0);
-                        entry.values.push(x.1);
+        match &q.filter {
+            Some(filter) => filter
+                .post_filters
+                .iter()
+                .all(|e| e.matches(&mut |field| self.get_field(field).ok())),
+            None => true,
+        }
+    }
+
+    fn read_row(&self, q: &Query) -> Vec<String> {
+        self.read_fields(&q.fields)
+    }
+
+    fn read_fields(&self, fields: &[String]) -> Vec<String> {
+        let mut row: Vec<String> = Vec::with_capacity(fields.len());
+
+        for field in fields.iter() {
+            match field.as_str() {
+                journal_fields::REALTIME => match self.backend.get_realtime_usec() {
+                    Ok(realtime) => row.push(realtime.to_string()),
+                    Err(JournalError::EndOfFile) => {
+                        panic!("should not return end of file")
+                    }
+                    Err(e) => {
+                        row.push(String::new());
+                        warn!("Could not get realtime field, error: {}", e);
+                    }
+                },
+                // Missing for entries imported without it
+                journal_fields::MONOTONIC => match self.backend.get_monotonic_usec() {
+                    Ok(monotonic) => row.push(monotonic.to_string()),
+                    Err(e) => {
+                        row.push(String::new());
+                        trace!("Could not get monotonic time, error: {}", e);
+                    }
+                },
+                journal_fields::CURSOR => match self.backend.get_cursor() {
+                    Ok(cursor) => row.push(cursor),
+                    Err(e) => {
+                        row.push(String::new());
+                        warn!("Could not get cursor, error: {}", e);
+                    }
+                },
+                _ => match self.get_field(field) {
+                    Ok(data) => {
+                        row.push(data);
+                    }
+                    Err(e) => {
+                        row.push(String::new());
+                        warn!("Could not find the field: {}, JournalError: {}", &field, e);
                     }
-                }
-                Err(err) => match err {
-                    JournalError::EndOfFile => break, // If we reach the end just ignore
-                    _ => return Err(err),             // Other type of error, return it
                 },
             }
         }
 
+        row
+    }
+
+    pub fn get_full_entry(&self, cursor: &str) -> Result<JournalEntry, JournalError> {
+        self.backend.flush_matches();
+        self.backend.seek_cursor(cursor)?;
+
+        // Seeking to a cursor that no longer exists lands on the closest entry
+        let more = self.backend.next()?;
+
+        if !more || !self.backend.test_cursor(cursor)? {
+            error!("Entry not found by the cursor");
+            return Err(JournalError::NotFound);
+        }
+
+        let mut entry = JournalEntry::new();
+
+        for (field, value) in self.backend.enumerate_data()? {
+            entry.headers.push(field);
+            entry
+                .values
+                .push(String::from_utf8_lossy(&value).into_owned());
+        }
+
+        entry.catalog = self.backend.get_catalog().unwrap_or_else(|e| {
+            warn!("Could not get the catalog of the entry, error: {}", e);
+            None
+        });
+
         Ok(entry)
     }
 
     fn get_field(&self, field: &str) -> Result<String, JournalError> {
-        sd_journal_get_data(self.ptr, field)
+        self.backend.get_data(field)
     }
 
     fn apply_pid_filter(&self, q: &Query) {
         if q.pid > 0 {
             let query = format!("{}={}", journal_fields::PID, q.pid);
-            if let Err(e) = sd_journal_add_match(self.ptr, query) {
+            if let Err(e) = self.backend.add_match(&query) {
                 warn!("Could not apply filter {}", e);
             }
         }
@@ -210,37 +865,106 @@ impl Journal {
         if !q.transports.is_empty() {
             for transport in q.transports.iter() {
                 let query = format!("{}={}", journal_fields::TRANSPORT, transport);
-                if let Err(e) = sd_journal_add_match(self.ptr, query) {
+                if let Err(e) = self.backend.add_match(&query) {
                     warn!("Could not apply filter {}", e);
                 }
             }
         }
     }
 
-    fn apply_minimum_priority(&self, q: &Query) {
-        for p in 0..=q.minimum_priority {
+    fn apply_priorities(&self, q: &Query) {
+        let priorities: Vec<u32> = if q.priorities.is_empty() {
+            (0..=q.minimum_priority).collect()
+        } else {
+            q.priorities.clone()
+        };
+
+        // Like journalctl, every priority means no filter, keeping the entries without
+        // a priority such as the ones of the audit transport
+        if (0..=7).all(|p| priorities.contains(&p)) {
+            return;
+        }
+
+        for p in priorities {
             let query = format!("{}={}", journal_fields::PRIORITY, p);
-            if let Err(e) = sd_journal_add_match(self.ptr, query) {
+            if let Err(e) = self.backend.add_match(&query) {
                 warn!("Could not apply filter {}", e);
             }
         }
     }
 
-    fn apply_units(&self, q: &Query) {
-        if !q.units.is_empty() {
-            for unit in q.units.iter() {
-                let query = format!("{}={}", journal_fields::UNIT_FILTER, unit);
-                if let Err(e) = sd_journal_add_match(self.ptr, query) {
-                    warn!("Could not apply filter {}", e);
-                }
+    fn apply_units(&self, q: &Query) -> Result<(), JournalError> {
+        if q.units.is_empty() && q.user_units.is_empty() {
+            return Ok(());
+        }
+
+        let mut group = vec![];
+        for unit in self.expand_units(&q.units, &unit::UNIT_FIELDS) {
+            if unit == INIT_UNIT {
+                group.push(vec![format!("{}=1", journal_fields::PID)]);
+            } else {
+                group.extend(unit::unit_matches(&unit));
+            }
+        }
+
+        for unit in self.expand_units(&q.user_units, &unit::USER_UNIT_FIELDS) {
+            group.extend(unit::user_unit_matches(&unit, q.user_uid));
+        }
+
+        self.add_match_group(&group)
+    }
+
+    /// Replaces glob patterns with the units in the journal they match, like journalctl.
+    /// A pattern matching none is kept as it is, so that it matches no entries either.
+    fn expand_units(&self, units: &[String], fields: &[&str]) -> Vec<String> {
+        let mut expanded: Vec<String> = vec![];
+        let mut logged_units: Option<Vec<String>> = None;
+
+        for pattern in units {
+            if !glob::is_glob(pattern) {
+                expanded.push(pattern.clone());
+                continue;
+            }
+
+            let logged_units = logged_units.get_or_insert_with(|| self.logged_units(fields));
+            let matching: Vec<&String> = logged_units
+                .iter()
+                .filter(|u| glob::glob_match(pattern, u))
+                .collect();
+            debug!("Unit pattern {} matches {} units", pattern, matching.len());
+
+            if matching.is_empty() {
+                expanded.push(pattern.clone());
+            } else {
+                expanded.extend(matching.into_iter().cloned());
             }
         }
+
+        expanded.sort();
+        expanded.dedup();
+        expanded
+    }
+
+    /// Units found in any of the fields
+    fn logged_units(&self, fields: &[&str]) -> Vec<String> {
+        let mut units = vec![];
+
+        for field in fields {
+            match self.backend.query_unique(field) {
+                Ok(values) => units.extend(values),
+                Err(e) => warn!("Could not list the values of {}: {}", field, e),
+            }
+        }
+
+        units.sort();
+        units.dedup();
+        units
     }
 
     fn apply_slice(&self, q: &Query) {
         if !q.slice.is_empty() {
             let query = format!("{}={}", journal_fields::SYSTEMD_SLICE, q.slice);
-            if let Err(e) = sd_journal_add_match(self.ptr, query) {
+            if let Err(e) = self.backend.add_match(&query) {
                 warn!("Could not apply filter {}", e);
             }
         }
@@ -250,57 +974,319 @@ impl Journal {
         if !q.boot_ids.is_empty() {
             for boot_id in q.boot_ids.iter() {
                 let query = format!("{}={}", journal_fields::BOOT_ID, boot_id);
-                if let Err(e) = sd_journal_add_match(self.ptr, query) {
+                if let Err(e) = self.backend.add_match(&query) {
                     warn!("Could not apply filter {}", e);
                 }
             }
         }
     }
 
-    pub fn list_services() -> Vec<Unit> {
-        let output = Command::new("systemctl")
-            .arg("list-unit-files")
-            .arg("*.service")
-            .arg("-o")
-            .arg("json")
-            .output()
-            .expect("Failed to execute command");
+    fn apply_namespaces(&self, q: &Query) {
+        // Entries without the field can't be matched, the namespaces are post filtered instead
+        if q.default_namespace {
+            return;
+        }
 
-        let stdout = String::from_utf8(output.stdout).unwrap();
+        for namespace in q.namespaces.iter() {
+            let query = format!("{}={}", journal_fields::NAMESPACE, namespace);
+            if let Err(e) = self.backend.add_match(&query) {
+                warn!("Could not apply filter {}", e);
+            }
+        }
+    }
 
-        let mut units: Vec<Unit> = serde_json::from_str(&stdout).unwrap();
-        units.insert(
-            0,
-            Unit {
-                unit_file: INIT_UNIT.into(),
-                state: String::new(),
-                preset: Option::None,
-            },
-        );
+    fn apply_current_boot(&self, boot_id: Option<String>) {
+        if let Some(boot_id) = boot_id {
+            let query = format!("{}={}", journal_fields::BOOT_ID, boot_id);
+            if let Err(e) = self.backend.add_match(&query) {
+                warn!("Could not apply filter {}", e);
+            }
+        }
+    }
 
-        units
+    fn apply_kernel_filter(&self, q: &Query) {
+        if let Some(kernel) = &q.kernel {
+            for query in kernel.matches() {
+                if let Err(e) = self.backend.add_match(&query) {
+                    warn!("Could not apply filter {}", e);
+                }
+            }
+        }
     }
 
-    pub fn list_boots() -> Vec<Boot> {
-        let output = Command::new("journalctl")
-            .arg("--list-boots")
-            .arg("-r")
-            .arg("-o")
-            .arg("json")
-            .output()
-            .expect("Failed to execute command");
+    /// Boot of the last entry, the running one when reading the local journal,
+    /// which is what `journalctl -b` shows
+    fn last_boot_id(&self) -> Option<String> {
+        self.backend.flush_matches();
 
-        let stdout = String::from_utf8(output.stdout).unwrap();
+        if let Err(e) = self.backend.seek_tail() {
+            warn!("Could not seek the last entry: {}", e);
+            return None;
+        }
 
-        serde_json::from_str(&stdout).unwrap()
+        match self.backend.previous() {
+            Ok(true) => self.get_field(journal_fields::BOOT_ID).ok(),
+            _ => None,
+        }
     }
-}
 
-impl Drop for Journal {
-    fn drop(&mut self) {
-        warn!("Dropping the journal");
-        sd_journal_close(self.ptr);
+    fn apply_filter_expression(&self, q: &Query) -> Result<(), JournalError> {
+        if let Some(filter) = &q.filter {
+            for group in filter.match_groups.iter() {
+                self.add_match_group(group)?;
+            }
+        }
+
+        Ok(())
     }
-}
 
-unsafe impl Send for Journal {}
+    /// Adds a disjunction of conjunctions of matches, ANDed with the matches added before and after.
+    /// On failure all the matches are flushed, as a partial group would match other entries.
+    fn add_match_group(&self, group: &[Vec<String>]) -> Result<(), JournalError> {
+        let added = self.push_match_group(group);
+
+        if let Err(e) = &added {
+            error!("Could not add a group of matches: {}", e);
+            self.backend.flush_matches();
+        }
+
+        added
+    }
+
+    fn push_match_group(&self, group: &[Vec<String>]) -> Result<(), JournalError> {
+        self.backend.add_conjunction()?;
+
+        for (i, conjunction) in group.iter().enumerate() {
+            if i > 0 {
+                self.backend.add_disjunction()?;
+            }
+
+            for m in conjunction {
+                self.backend.add_match(m)?;
+            }
+        }
+
+        self.backend.add_conjunction()?;
+
+        Ok(())
+    }
+
+    /// Lists the boots found in the journal, most recent first,
+    /// indexed like `journalctl --list-boots` with 0 as the last boot
+    pub fn list_boots(&self) -> Result<Vec<Boot>, JournalError> {
+        let boot_ids = self.backend.query_unique(journal_fields::BOOT_ID)?;
+        let mut boots: Vec<Boot> = Vec::with_capacity(boot_ids.len());
+
+        for boot_id in boot_ids {
+            self.backend.flush_matches();
+            self.backend
+                .add_match(&format!("{}={}", journal_fields::BOOT_ID, boot_id))?;
+
+            self.backend.seek_head()?;
+            if !self.backend.next()? {
+                continue;
+            }
+
+            let first_entry = self.backend.get_realtime_usec()?;
+            let hostname = self.get_field(journal_fields::HOSTNAME).ok();
+
+            let mut last_entry: u64 = 0;
+            self.backend.seek_tail()?;
+            if self.backend.previous()? {
+                last_entry = self.backend.get_realtime_usec()?;
+            }
+
+            let kernel_version = self.find_kernel_version()?;
+
+            boots.push(Boot {
+                index: 0,
+                boot_id,
+                first_entry: first_entry as i64,
+                last_entry: last_entry as i64,
+                entry_count: None,
+                kernel_version,
+                hostname,
+            });
+        }
+
+        self.backend.flush_matches();
+
+        boots.sort_by_key(|b| std::cmp::Reverse(b.first_entry));
+        for (i, boot) in boots.iter_mut().enumerate() {
+            boot.index = -(i as i32);
+        }
+
+        Ok(boots)
+    }
+
+    /// Number of entries of a boot, which reads every one of them
+    pub fn count_boot_entries(&self, boot_id: &str) -> Result<u64, JournalError> {
+        self.backend.flush_matches();
+        self.backend
+            .add_match(&format!("{}={}", journal_fields::BOOT_ID, boot_id))?;
+        self.backend.seek_head()?;
+
+        let mut count: u64 = 0;
+        while self.backend.next()? {
+            count += 1;
+        }
+        self.backend.flush_matches();
+
+        Ok(count)
+    }
+
+    /// Lists the crashes logged by systemd-coredump, most recent first,
+    /// in the given boot or in all of them
+    pub fn list_coredumps(&self, boot_id: Option<&str>) -> Result<Vec<Coredump>, JournalError> {
+        self.backend.flush_matches();
+        self.backend.add_match(&format!(
+            "{}={}",
+            journal_fields::MESSAGE_ID,
+            journal_fields::COREDUMP_MESSAGE_ID
+        ))?;
+        if let Some(boot_id) = boot_id {
+            self.backend
+                .add_match(&format!("{}={}", journal_fields::BOOT_ID, boot_id))?;
+        }
+
+        self.backend.seek_tail()?;
+
+        let mut coredumps = vec![];
+        while self.backend.previous()? {
+            coredumps.push(self.read_coredump()?);
+        }
+
+        self.backend.flush_matches();
+
+        Ok(coredumps)
+    }
+
+    /// The crash logged in the entry of the cursor
+    pub fn get_coredump(&self, cursor: &str) -> Result<Coredump, JournalError> {
+        self.backend.flush_matches();
+        self.backend.seek_cursor(cursor)?;
+
+        if !self.backend.next()? || !self.backend.test_cursor(cursor)? {
+            return Err(JournalError::NotFound);
+        }
+
+        if self.get_field(journal_fields::MESSAGE_ID).ok().as_deref()
+            != Some(journal_fields::COREDUMP_MESSAGE_ID)
+        {
+            return Err(JournalError::InvalidQuery(
+                "the entry is not a coredump".into(),
+            ));
+        }
+
+        self.read_coredump()
+    }
+
+    fn read_coredump(&self) -> Result<Coredump, JournalError> {
+        Ok(Coredump::from_fields(
+            self.backend.get_cursor()?,
+            self.backend.get_realtime_usec()?,
+            |field| self.get_field(field).ok(),
+        ))
+    }
+
+    /// Events of the kernel audit subsystem, with their records parsed and grouped
+    /// by serial number. The query is expected to be built `with_audit`.
+    pub fn list_audit_events(&self, q: &Query) -> Result<Vec<AuditEvent>, JournalError> {
+        let records = self
+            .query_logs(q)?
+            .rows
+            .iter()
+            .map(|row| audit::record_from_row(row))
+            .collect();
+
+        Ok(audit::group_events(records))
+    }
+
+    /// Reads the kernel version from the banner logged by the kernel at the start of the boot,
+    /// expects the matches for the boot to be applied already
+    fn find_kernel_version(&self) -> Result<Option<String>, JournalError> {
+        const KERNEL_BANNER: &str = "Linux version ";
+        // The banner is one of the first kernel messages, don't scan the whole boot
+        const MAX_ENTRIES_TO_CHECK: usize = 20;
+
+        self.backend
+            .add_match(&format!("{}=kernel", journal_fields::TRANSPORT))?;
+        self.backend.seek_head()?;
+
+        for _ in 0..MAX_ENTRIES_TO_CHECK {
+            if !self.backend.next()? {
+                break;
+            }
+
+            if let Ok(message) = self.get_field(MESSAGE) {
+                if let Some(banner) = message.strip_prefix(KERNEL_BANNER) {
+                    return Ok(banner.split_whitespace().next().map(String::from));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Lists every field name used by the entries in the journal, sorted,
+    /// including the pseudo fields that can be requested as columns
+    pub fn list_fields(&self) -> Result<Vec<FieldInfo>, JournalError> {
+        let mut names = self.backend.enumerate_fields()?;
+        names.sort_unstable();
+
+        let fields = [
+            journal_fields::REALTIME,
+            journal_fields::MONOTONIC,
+            journal_fields::CURSOR,
+        ]
+        .into_iter()
+        .map(String::from)
+        .chain(names)
+        .map(|name| FieldInfo {
+            description: journal_fields::describe(&name).map(String::from),
+            name,
+        })
+        .collect();
+
+        Ok(fields)
+    }
+
+    /// Units of the system and of the user's service manager, starting with the init pseudo unit.
+    /// The units that logged are listed along with the unit files of the running system.
+    pub fn list_services(&self, source: &JournalSource) -> Result<Vec<Unit>, JournalError> {
+        let mut units = vec![Unit {
+            unit_file: INIT_UNIT.into(),
+            state: String::new(),
+            preset: Option::None,
+            unit_type: UnitType::Service,
+            user: false,
+        }];
+
+        if source.is_local() {
+            units.extend(unit::list_unit_files(false));
+            units.extend(unit::list_unit_files(true));
+        }
+
+        for (field, user) in [
+            (journal_fields::SYSTEMD_UNIT, false),
+            (journal_fields::SYSTEMD_USER_UNIT, true),
+        ] {
+            for name in self.get_field_values(field)? {
+                if !units.iter().any(|u| u.user == user && u.unit_file == name) {
+                    units.extend(Unit::logged(&name, user));
+                }
+            }
+        }
+
+        Ok(units)
+    }
+
+    /// Lists the distinct values of a field across the whole journal, sorted,
+    /// e.g. all the units that logged something for `_SYSTEMD_UNIT`
+    pub fn get_field_values(&self, field: &str) -> Result<Vec<String>, JournalError> {
+        let mut values = self.backend.query_unique(field)?;
+        values.sort_unstable();
+
+        Ok(values)
+    }
+}
@@ -0,0 +1,107 @@
+use super::storage::JOURNAL_DIRS;
+use std::path::{Path, PathBuf};
+
+/// Directory with the journal files of a machine in a namespace, named
+/// `<machine-id>` for the default namespace and `<machine-id>.<namespace>` for the others
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct NamespaceDir {
+    pub path: PathBuf,
+    pub machine_id: String,
+    /// None for the default namespace
+    pub namespace: Option<String>,
+}
+
+/// Namespaces of the running system with journal files, besides the default one
+pub(crate) fn list_namespaces() -> Vec<String> {
+    find_namespaces(&JOURNAL_DIRS)
+}
+
+fn find_namespaces(roots: &[&str]) -> Vec<String> {
+    let mut namespaces: Vec<String> = namespace_dirs(roots)
+        .into_iter()
+        .filter_map(|d| d.namespace)
+        .collect();
+    namespaces.sort();
+    namespaces.dedup();
+
+    namespaces
+}
+
+/// Directories of each machine and namespace within the journal directories
+pub(crate) fn namespace_dirs(roots: &[&str]) -> Vec<NamespaceDir> {
+    let mut dirs = vec![];
+
+    for root in roots {
+        let Ok(entries) = std::fs::read_dir(Path::new(root)) else {
+            continue;
+        };
+
+        let mut found: Vec<NamespaceDir> = entries
+            .flatten()
+            .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+            .filter_map(|e| {
+                let name = e.file_name().to_string_lossy().into_owned();
+                let (machine_id, namespace) = parse_dir_name(&name)?;
+
+                Some(NamespaceDir {
+                    path: e.path(),
+                    machine_id: machine_id.to_owned(),
+                    namespace: namespace.map(String::from),
+                })
+            })
+            .collect();
+        found.sort_by(|a, b| a.path.cmp(&b.path));
+        dirs.extend(found);
+    }
+
+    dirs
+}
+
+/// Machine id and namespace of a directory name, None if it is not a journal directory
+fn parse_dir_name(name: &str) -> Option<(&str, Option<&str>)> {
+    let (machine_id, namespace) = match name.split_once('.') {
+        Some((machine_id, namespace)) if !namespace.is_empty() => (machine_id, Some(namespace)),
+        Some(_) => return None,
+        None => (name, None),
+    };
+
+    let is_machine_id = machine_id.len() == 32
+        && machine_id
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c));
+
+    is_machine_id.then_some((machine_id, namespace))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_namespaces, namespace_dirs, parse_dir_name};
+
+    #[test]
+    fn directory_names() {
+        assert_eq!(
+            parse_dir_name("5a9c0e4d7b3f4e21a8d6c1f09b2e7d43"),
+            Some(("5a9c0e4d7b3f4e21a8d6c1f09b2e7d43", None))
+        );
+        assert_eq!(
+            parse_dir_name("5a9c0e4d7b3f4e21a8d6c1f09b2e7d43.highvolume"),
+            Some(("5a9c0e4d7b3f4e21a8d6c1f09b2e7d43", Some("highvolume")))
+        );
+        assert_eq!(parse_dir_name("5a9c0e4d7b3f4e21a8d6c1f09b2e7d43."), None);
+        assert_eq!(parse_dir_name("remote"), None);
+        assert_eq!(parse_dir_name("5A9C0E4D7B3F4E21A8D6C1F09B2E7D43"), None);
+    }
+
+    #[test]
+    fn namespaces_of_the_journal_directories() {
+        let roots = ["./tests/fixtures/journal", "./tests/fixtures/no-such-dir"];
+
+        let dirs = namespace_dirs(&roots);
+        assert_eq!(dirs.len(), 2);
+        assert_eq!(dirs[0].namespace, None);
+        assert_eq!(dirs[1].namespace.as_deref(), Some("highvolume"));
+        assert_eq!(dirs[1].machine_id, "5a9c0e4d7b3f4e21a8d6c1f09b2e7d43");
+
+        assert_eq!(find_namespaces(&roots), vec!["highvolume"]);
+    }
+}
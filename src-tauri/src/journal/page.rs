@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+
+/// Entry a page of entries is read around, or the page token to continue from
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Anchor {
+    /// The entry of the cursor, or the closest one matching the query after it
+    Cursor { cursor: String },
+    /// The first entry at or after the time in microseconds since the epoch
+    Realtime { realtime: u64 },
+    /// Entries older than the page the token was returned with
+    Older { token: String },
+    /// Entries newer than the page the token was returned with
+    Newer { token: String },
+}
+
+/// Entries around an anchor, newest first like `JournalEntries`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalPage {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// Index of the anchor entry in the rows
+    pub anchor: Option<usize>,
+    /// Token for the entries newer than the page, none if there are no more
+    pub newer: Option<String>,
+    /// Token for the entries older than the page, none if there are no more
+    pub older: Option<String>,
+}
+
+/// Entries read in one direction from the anchor, closest first
+#[derive(Debug, Default)]
+pub(crate) struct PageRows {
+    pub(crate) cursors: Vec<String>,
+    pub(crate) rows: Vec<Vec<String>>,
+    /// Whether more entries follow the ones read
+    pub(crate) more: bool,
+}
+
+impl PageRows {
+    /// Rows on the side a page token continues from, known to have more entries
+    pub(crate) fn continued() -> PageRows {
+        PageRows {
+            more: true,
+            ..PageRows::default()
+        }
+    }
+
+    pub(crate) fn push(&mut self, cursor: String, row: Vec<String>) {
+        self.cursors.push(cursor);
+        self.rows.push(row);
+    }
+}
+
+impl JournalPage {
+    pub(crate) fn new(
+        headers: Vec<String>,
+        newer: PageRows,
+        anchor: Option<(String, Vec<String>)>,
+        older: PageRows,
+    ) -> JournalPage {
+        let mut cursors: Vec<String> = newer.cursors.into_iter().rev().collect();
+        let mut rows: Vec<Vec<String>> = newer.rows.into_iter().rev().collect();
+        let mut anchor_index = None;
+
+        if let Some((cursor, row)) = anchor {
+            anchor_index = Some(rows.len());
+            cursors.push(cursor);
+            rows.push(row);
+        }
+
+        cursors.extend(older.cursors);
+        rows.extend(older.rows);
+
+        JournalPage {
+            headers,
+            rows,
+            anchor: anchor_index,
+            newer: cursors.first().filter(|_| newer.more).cloned(),
+            older: cursors.last().filter(|_| older.more).cloned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Anchor;
+    use crate::journal::testing::{self, entry};
+    use crate::journal::{ImportedEntry, Journal, MemoryJournal, QueryBuilder};
+
+    /// Entries every 100 microseconds, with the cursors numbered from c1
+    fn journal() -> Journal<MemoryJournal> {
+        testing::journal(
+            (1..=10)
+                .map(|i| ImportedEntry {
+                    cursor: format!("c{}", i),
+                    ..entry(
+                        i * 100,
+                        &[
+                            ("MESSAGE", &format!("message {}", i)),
+                            ("PRIORITY", if i % 2 == 0 { "3" } else { "6" }),
+                            ("_TRANSPORT", "journal"),
+                        ],
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    fn page(
+        j: &Journal<MemoryJournal>,
+        qb: &mut QueryBuilder,
+        anchor: Anchor,
+        before: u64,
+        after: u64,
+    ) -> (Vec<String>, Option<usize>, Option<String>, Option<String>) {
+        let q = qb.with_fields(vec!["MESSAGE".into()]).build();
+        let page = j.get_page(&q, &anchor, before, after).unwrap();
+        let messages = page.rows.into_iter().map(|r| r[0].clone()).collect();
+
+        (messages, page.anchor, page.newer, page.older)
+    }
+
+    fn all_priorities() -> QueryBuilder {
+        let mut qb = QueryBuilder::default();
+        qb.with_priority_above_or_equal_to(7);
+        qb
+    }
+
+    #[test]
+    fn around_a_cursor() {
+        let j = journal();
+
+        let (messages, anchor, newer, older) = page(
+            &j,
+            &mut all_priorities(),
+            Anchor::Cursor {
+                cursor: "c5".into(),
+            },
+            2,
+            1,
+        );
+        assert_eq!(
+            messages,
+            vec!["message 6", "message 5", "message 4", "message 3"]
+        );
+        assert_eq!(anchor, Some(1));
+        assert_eq!(newer.as_deref(), Some("c6"));
+        assert_eq!(older.as_deref(), Some("c3"));
+    }
+
+    #[test]
+    fn around_a_time_at_the_edges() {
+        let j = journal();
+
+        // Between two entries, the anchor is the next one
+        let (messages, anchor, newer, older) = page(
+            &j,
+            &mut all_priorities(),
+            Anchor::Realtime { realtime: 150 },
+            5,
+            0,
+        );
+        assert_eq!(messages, vec!["message 2", "message 1"]);
+        assert_eq!(anchor, Some(0));
+        assert_eq!(newer.as_deref(), Some("c2"));
+        assert_eq!(older, None);
+
+        // After the last entry, the anchor is the last one
+        let (messages, anchor, newer, _) = page(
+            &j,
+            &mut all_priorities(),
+            Anchor::Realtime { realtime: 5000 },
+            1,
+            3,
+        );
+        assert_eq!(messages, vec!["message 10", "message 9"]);
+        assert_eq!(anchor, Some(0));
+        assert_eq!(newer, None);
+    }
+
+    #[test]
+    fn continues_with_tokens() {
+        let j = journal();
+
+        let (messages, anchor, newer, older) = page(
+            &j,
+            &mut all_priorities(),
+            Anchor::Older { token: "c3".into() },
+            5,
+            0,
+        );
+        assert_eq!(messages, vec!["message 2", "message 1"]);
+        assert_eq!(anchor, None);
+        assert_eq!(newer.as_deref(), Some("c2"));
+        assert_eq!(older, None);
+
+        let (messages, _, newer, older) = page(
+            &j,
+            &mut all_priorities(),
+            Anchor::Newer { token: "c3".into() },
+            0,
+            3,
+        );
+        assert_eq!(messages, vec!["message 6", "message 5", "message 4"]);
+        assert_eq!(newer.as_deref(), Some("c6"));
+        assert_eq!(older.as_deref(), Some("c4"));
+    }
+
+    #[test]
+    fn filters_and_date_range() {
+        let j = journal();
+
+        // Only the even entries have a priority of error
+        let mut qb = QueryBuilder::default();
+        qb.with_priority_above_or_equal_to(3)
+            .with_date_more_than(200)
+            .with_date_less_than(800);
+
+        let (messages, anchor, newer, older) = page(
+            &j,
+            &mut qb,
+            Anchor::Cursor {
+                cursor: "c5".into(),
+            },
+            5,
+            5,
+        );
+        assert_eq!(messages, vec!["message 6", "message 4"]);
+        assert_eq!(anchor, Some(0));
+        assert_eq!(newer, None);
+        assert_eq!(older, None);
+    }
+}
@@ -1,15 +1,35 @@
+use super::exclusions::Exclusions;
+use super::filter_expression::CompiledFilter;
+use super::kernel::KernelFilter;
+use super::search::Search;
+
 #[derive(Debug)]
 pub struct Query {
     pub(crate) pid: u32,
     pub(crate) fields: Vec<String>,
     pub(crate) minimum_priority: u32,
+    /// Exact set of priorities, replaces the minimum priority when not empty
+    pub(crate) priorities: Vec<u32>,
     pub(crate) units: Vec<String>,
+    pub(crate) user_units: Vec<String>,
+    /// User whose service manager runs the user units
+    pub(crate) user_uid: u32,
     pub(crate) slice: String,
     pub(crate) limit: u64,
     pub(crate) date_less_than: u64,
     pub(crate) date_more_than: u64,
     pub(crate) transports: Vec<String>,
-    pub(crate) quick_search: String,
+    pub(crate) search: Option<Search>,
     pub(crate) reset_position: bool,
+    pub(crate) cursor: String,
+    pub(crate) filter: Option<CompiledFilter>,
     pub(crate) boot_ids: Vec<String>,
+    /// Journal namespaces, only narrowing down the ones the journal was opened with
+    pub(crate) namespaces: Vec<String>,
+    /// Keeps the entries of the default namespace along with the ones of `namespaces`
+    pub(crate) default_namespace: bool,
+    /// Only the last boot when no boot ids are given
+    pub(crate) current_boot: bool,
+    pub(crate) kernel: Option<KernelFilter>,
+    pub(crate) exclusions: Exclusions,
 }
@@ -1,6 +1,7 @@
 use std::mem;
 
-use super::{journal_fields, query::Query};
+use super::audit::AUDIT_FIELDS;
+use super::{Exclusions, FilterExpression, KernelFilter, Search, journal_fields, query::Query};
 
 pub struct QueryBuilder {
     query: Query,
@@ -12,15 +13,25 @@ impl QueryBuilder {
             pid: 0,
             fields: vec![],
             minimum_priority: 4,
+            priorities: vec![],
             units: vec![],
+            user_units: vec![],
+            user_uid: 0,
             slice: String::new(),
             boot_ids: vec![],
+            namespaces: vec![],
+            default_namespace: false,
+            current_boot: false,
+            kernel: None,
             limit: 100,
             transports: vec!["syslog".into(), "journal".into(), "stdout".into()],
             date_less_than: 0,
             date_more_than: 0,
-            quick_search: String::new(),
+            search: None,
             reset_position: true,
+            cursor: String::new(),
+            filter: None,
+            exclusions: Exclusions::default(),
         };
 
         let mut qb = QueryBuilder { query };
@@ -75,7 +86,16 @@ impl QueryBuilder {
     }
 
     pub fn with_quick_search(&mut self, quick_search: String) -> &mut Self {
-        self.query.quick_search = quick_search.to_lowercase();
+        if quick_search.is_empty() {
+            self.query.search = None;
+        } else {
+            self.query.search = Some(Search::message(&quick_search));
+        }
+        self
+    }
+
+    pub fn with_search(&mut self, search: Search) -> &mut Self {
+        self.query.search = Some(search);
         self
     }
 
@@ -84,6 +104,12 @@ impl QueryBuilder {
         self
     }
 
+    /// Continues reading before the entry with the given cursor, excluding it
+    pub fn with_cursor(&mut self, cursor: String) -> &mut Self {
+        self.query.cursor = cursor;
+        self
+    }
+
     pub fn with_date_less_than(&mut self, from_epoch: u64) -> &mut Self {
         self.query.date_less_than = from_epoch;
         self
@@ -104,11 +130,24 @@ impl QueryBuilder {
         self
     }
 
+    /// Only the given priorities, e.g. `[4]` for warnings alone, instead of a minimum priority
+    pub fn with_priorities(&mut self, priorities: Vec<u32>) -> &mut Self {
+        self.query.priorities = priorities.into_iter().filter(|p| *p <= 7).collect();
+        self
+    }
+
     pub fn with_units(&mut self, units: Vec<String>) -> &mut Self {
         self.query.units = units;
         self
     }
 
+    /// Units of the service manager of the user with the uid, like `journalctl --user-unit`
+    pub fn with_user_units(&mut self, user_units: Vec<String>, uid: u32) -> &mut Self {
+        self.query.user_units = user_units;
+        self.query.user_uid = uid;
+        self
+    }
+
     pub fn within_slice(&mut self, slice: &str) -> &mut Self {
         self.query.slice = String::from(slice);
         self
@@ -119,10 +158,55 @@ impl QueryBuilder {
         self
     }
 
+    /// Entries of the given journal namespaces, which the journal has to be opened with
+    pub fn with_namespaces(&mut self, namespaces: Vec<String>) -> &mut Self {
+        self.query.namespaces = namespaces;
+        self
+    }
+
+    /// Entries of the default namespace too, which have no `_NAMESPACE` field to match on
+    pub fn with_default_namespace(&mut self, default_namespace: bool) -> &mut Self {
+        self.query.default_namespace = default_namespace;
+        self
+    }
+
+    /// Kernel messages of the current boot, narrowed down by device, like `journalctl -k -b`
+    pub fn with_kernel(&mut self, filter: KernelFilter) -> &mut Self {
+        self.query.transports = vec!["kernel".into()];
+        self.query.current_boot = true;
+        self.query.kernel = Some(filter);
+        self
+    }
+
+    /// Records of the kernel audit subsystem, which have no priority
+    pub fn with_audit(&mut self) -> &mut Self {
+        self.query.transports = vec!["audit".into()];
+        self.query.minimum_priority = 7;
+        self.query.priorities.clear();
+        self.query.fields = AUDIT_FIELDS.iter().map(|f| f.to_string()).collect();
+        self
+    }
+
+    pub fn with_filter(&mut self, filter: FilterExpression) -> &mut Self {
+        self.query.filter = Some(filter.compile());
+        self
+    }
+
+    pub fn with_exclusions(&mut self, exclusions: Exclusions) -> &mut Self {
+        self.query.exclusions = exclusions;
+        self
+    }
+
     pub fn build(&mut self) -> Query {
         let qb = QueryBuilder::default();
         let old_qb = mem::replace(self, qb);
+        let mut query = old_qb.query;
+
+        // Every row carries its cursor so that it can be identified later on
+        if !query.fields.iter().any(|f| f == journal_fields::CURSOR) {
+            query.fields.push(journal_fields::CURSOR.to_owned());
+        }
 
-        old_qb.query
+        query
     }
 }
@@ -0,0 +1,148 @@
+use super::error::JournalError;
+use super::journal_fields;
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+
+/// How the quick search text is matched against the field value
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchMode {
+    /// Case insensitive substring
+    #[default]
+    Insensitive,
+    /// Case sensitive substring
+    CaseSensitive,
+    /// Regular expression, matching anywhere in the value
+    Regex,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    Insensitive(String),
+    CaseSensitive(String),
+    Regex(Regex),
+}
+
+/// Quick search over a single field of the entries, `MESSAGE` unless scoped
+#[derive(Debug, Clone)]
+pub struct Search {
+    field: String,
+    matcher: Matcher,
+}
+
+impl Search {
+    /// Case insensitive search of the text in the message
+    pub fn message(text: &str) -> Search {
+        Search {
+            field: journal_fields::MESSAGE.to_owned(),
+            matcher: Matcher::Insensitive(text.to_lowercase()),
+        }
+    }
+
+    /// Parses the quick search text. A `FIELD:text` prefix searches another field defined
+    /// by systemd and `FIELD:~pattern` searches it with a regular expression, e.g. `_EXE:~/usr/bin/.*`
+    pub fn parse(text: &str, mode: SearchMode) -> Result<Search, JournalError> {
+        let (field, text, mode) = match split_field_scope(text) {
+            Some((field, scoped)) => match scoped.strip_prefix('~') {
+                Some(pattern) => (field, pattern, SearchMode::Regex),
+                None => (field, scoped, mode),
+            },
+            None => (journal_fields::MESSAGE, text, mode),
+        };
+
+        let matcher = match mode {
+            SearchMode::Insensitive => Matcher::Insensitive(text.to_lowercase()),
+            SearchMode::CaseSensitive => Matcher::CaseSensitive(text.to_owned()),
+            SearchMode::Regex => Matcher::Regex(
+                RegexBuilder::new(text)
+                    .size_limit(1 << 20)
+                    .build()
+                    .map_err(|e| JournalError::InvalidSearch(e.to_string()))?,
+            ),
+        };
+
+        Ok(Search {
+            field: field.to_owned(),
+            matcher,
+        })
+    }
+
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    pub fn is_match(&self, value: &str) -> bool {
+        match &self.matcher {
+            Matcher::Insensitive(text) => value.to_lowercase().contains(text),
+            Matcher::CaseSensitive(text) => value.contains(text),
+            Matcher::Regex(regex) => regex.is_match(value),
+        }
+    }
+}
+
+/// Splits `FIELD:text` when the prefix is one of the journal fields, so that messages
+/// such as `ACPI: ...` or `ERROR: ...` are still searched as they are
+fn split_field_scope(text: &str) -> Option<(&str, &str)> {
+    let (field, rest) = text.split_once(':')?;
+
+    journal_fields::describe(field).map(|_| (field, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Search, SearchMode};
+    use crate::journal::JournalError;
+
+    #[test]
+    fn insensitive_by_default() {
+        let s = Search::parse("Failed", SearchMode::default()).unwrap();
+        assert_eq!(s.field(), "MESSAGE");
+        assert!(s.is_match("Unit failed to start"));
+
+        let s = Search::parse("Failed", SearchMode::CaseSensitive).unwrap();
+        assert!(!s.is_match("Unit failed to start"));
+        assert!(s.is_match("Failed to start"));
+    }
+
+    #[test]
+    fn regex() {
+        let s = Search::parse(r"^Started .*\.service$", SearchMode::Regex).unwrap();
+        assert!(s.is_match("Started nginx.service"));
+        assert!(!s.is_match("Stopped nginx.service"));
+    }
+
+    #[test]
+    fn field_scoped() {
+        let s = Search::parse("_EXE:~/usr/bin/.*", SearchMode::Insensitive).unwrap();
+        assert_eq!(s.field(), "_EXE");
+        assert!(s.is_match("/usr/bin/bash"));
+        assert!(!s.is_match("/usr/lib/systemd/systemd"));
+
+        let s = Search::parse("_COMM:SSHD", SearchMode::Insensitive).unwrap();
+        assert_eq!(s.field(), "_COMM");
+        assert!(s.is_match("sshd"));
+
+        // Not a field name, search the message
+        let s = Search::parse("error: disk full", SearchMode::Insensitive).unwrap();
+        assert_eq!(s.field(), "MESSAGE");
+        assert!(s.is_match("Error: disk full"));
+
+        // Nor are the prefixes of kernel and program messages
+        let s = Search::parse("ACPI: Added _OSI", SearchMode::CaseSensitive).unwrap();
+        assert_eq!(s.field(), "MESSAGE");
+        assert!(s.is_match("ACPI: Added _OSI(Module Device)"));
+
+        let s = Search::parse("ERROR: relation", SearchMode::Insensitive).unwrap();
+        assert_eq!(s.field(), "MESSAGE");
+        assert!(s.is_match("ERROR: relation \"users\" does not exist"));
+    }
+
+    #[test]
+    fn invalid_regex() {
+        let e = Search::parse("MESSAGE:~(unclosed", SearchMode::Insensitive).unwrap_err();
+        assert!(matches!(e, JournalError::InvalidSearch(_)));
+
+        let e = Search::parse("[a-", SearchMode::Regex).unwrap_err();
+        assert!(matches!(e, JournalError::InvalidSearch(_)));
+    }
+}
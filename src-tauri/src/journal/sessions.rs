@@ -0,0 +1,143 @@
+use super::error::JournalError;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Identifies a session, handed out by `Sessions::open`
+pub type SessionId = u64;
+
+/// Registry of handles owned by each client view, so that their read positions don't
+/// interfere. Sessions not used for longer than the idle timeout are closed on the next access.
+#[derive(Debug)]
+pub struct Sessions<T> {
+    sessions: HashMap<SessionId, Session<T>>,
+    next_id: SessionId,
+    idle_timeout: Duration,
+}
+
+#[derive(Debug)]
+struct Session<T> {
+    handle: Arc<T>,
+    last_used: Instant,
+}
+
+impl<T> Sessions<T> {
+    pub fn new(idle_timeout: Duration) -> Sessions<T> {
+        Sessions {
+            sessions: HashMap::new(),
+            next_id: 1,
+            idle_timeout,
+        }
+    }
+
+    pub fn open(&mut self, handle: T) -> SessionId {
+        self.open_at(handle, Instant::now())
+    }
+
+    /// Handle of the session, which is kept alive by the returned reference
+    /// even if the session is closed meanwhile
+    pub fn get(&mut self, id: SessionId) -> Result<Arc<T>, JournalError> {
+        self.get_at(id, Instant::now())
+    }
+
+    /// Returns false if the session was already closed or evicted
+    pub fn close(&mut self, id: SessionId) -> bool {
+        self.sessions.remove(&id).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.sessions.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    fn open_at(&mut self, handle: T, now: Instant) -> SessionId {
+        self.evict_idle(now);
+
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.sessions.insert(
+            id,
+            Session {
+                handle: Arc::new(handle),
+                last_used: now,
+            },
+        );
+
+        id
+    }
+
+    fn get_at(&mut self, id: SessionId, now: Instant) -> Result<Arc<T>, JournalError> {
+        self.evict_idle(now);
+
+        let session = self
+            .sessions
+            .get_mut(&id)
+            .ok_or(JournalError::SessionNotFound(id))?;
+        session.last_used = now;
+
+        Ok(session.handle.clone())
+    }
+
+    fn evict_idle(&mut self, now: Instant) {
+        let idle_timeout = self.idle_timeout;
+
+        self.sessions.retain(|id, session| {
+            let alive = now.saturating_duration_since(session.last_used) <= idle_timeout;
+            if !alive {
+                debug!("Evicting idle session {}", id);
+            }
+            alive
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sessions;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn independent_handles() {
+        let mut sessions = Sessions::new(Duration::from_secs(60));
+        let a = sessions.open("a");
+        let b = sessions.open("b");
+
+        assert_ne!(a, b);
+        assert_eq!(*sessions.get(a).unwrap(), "a");
+        assert_eq!(*sessions.get(b).unwrap(), "b");
+
+        assert!(sessions.close(a));
+        assert!(!sessions.close(a));
+        assert!(sessions.get(a).is_err());
+        assert_eq!(sessions.len(), 1);
+
+        // Ids are not reused after closing
+        assert!(sessions.open("c") > b);
+    }
+
+    #[test]
+    fn evicts_idle_sessions() {
+        let start = Instant::now();
+        let minute = Duration::from_secs(60);
+        let mut sessions = Sessions::new(minute * 10);
+
+        let idle = sessions.open_at("idle", start);
+        let busy = sessions.open_at("busy", start);
+
+        sessions.get_at(busy, start + minute * 8).unwrap();
+        assert_eq!(sessions.len(), 2);
+
+        // Using one session keeps it alive, the other one is evicted
+        sessions.get_at(busy, start + minute * 16).unwrap();
+        assert!(sessions.get_at(idle, start + minute * 16).is_err());
+        assert_eq!(sessions.len(), 1);
+    }
+}
@@ -0,0 +1,284 @@
+use super::JournalSource;
+use super::journal_file::{FileState, Header, hex};
+use super::journald_conf::JournaldConfig;
+use super::namespace::namespace_dirs;
+use serde::Serialize;
+use std::io::Read;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+/// Directories journald writes to, with a directory per machine id and namespace
+pub(crate) const JOURNAL_DIRS: [&str; 2] = ["/var/log/journal", "/run/log/journal"];
+
+/// Bytes read to parse the header, larger than any header written so far
+const HEADER_READ_SIZE: u64 = 4096;
+
+/// Disk usage of a journal, to find out why old entries are gone
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageReport {
+    /// Bytes on disk of the journal files libsystemd reads
+    pub usage: u64,
+    /// Every journal file found, oldest first
+    pub files: Vec<JournalFileInfo>,
+    /// Configuration of journald, only known for the journal of the running system
+    pub config: Option<JournaldConfig>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JournalFileState {
+    /// Being written, or left open by a journald that did not stop cleanly
+    Online,
+    /// Closed cleanly, it may still be written to
+    Offline,
+    /// Rotated, it won't be written again
+    Archived,
+    /// Renamed to `*.journal~` by journald after finding it corrupted or not closed
+    /// cleanly, or a file whose header can't be read
+    Corrupt,
+}
+
+/// Journal file on disk, described from its header
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalFileInfo {
+    pub path: String,
+    /// Bytes allocated on disk, journal files are sparse
+    pub size: u64,
+    pub state: JournalFileState,
+    /// Why the header could not be read
+    pub error: Option<String>,
+    pub machine_id: Option<String>,
+    pub entries: Option<u64>,
+    /// Time of the first entry in microseconds since the epoch
+    pub first_entry: Option<u64>,
+    /// Time of the last entry in microseconds since the epoch
+    pub last_entry: Option<u64>,
+    pub first_seqnum: Option<u64>,
+    pub last_seqnum: Option<u64>,
+    /// `zstd`, `lz4` or `xz`, None when the data is not compressed
+    pub compression: Option<String>,
+    /// Written in the compact format of systemd 252 and later
+    pub compact: bool,
+}
+
+impl JournalFileInfo {
+    fn read(path: &Path) -> JournalFileInfo {
+        let size = std::fs::metadata(path)
+            .map(|m| m.blocks() * 512)
+            .unwrap_or(0);
+        let corrupt = path.extension().is_some_and(|e| e == "journal~");
+
+        let mut info = JournalFileInfo {
+            path: path.to_string_lossy().into_owned(),
+            size,
+            state: JournalFileState::Corrupt,
+            error: None,
+            machine_id: None,
+            entries: None,
+            first_entry: None,
+            last_entry: None,
+            first_seqnum: None,
+            last_seqnum: None,
+            compression: None,
+            compact: false,
+        };
+
+        let header = match read_header(path) {
+            Ok(header) => header,
+            Err(e) => {
+                info.error = Some(e);
+                return info;
+            }
+        };
+
+        if !corrupt {
+            info.state = match header.state {
+                FileState::Online => JournalFileState::Online,
+                FileState::Offline => JournalFileState::Offline,
+                FileState::Archived => JournalFileState::Archived,
+            };
+        }
+
+        // Files without entries have zeros in place of the ranges
+        let non_zero = |value: u64| (value > 0).then_some(value);
+
+        info.machine_id = Some(hex(&header.machine_id));
+        info.entries = Some(header.n_entries);
+        info.first_entry = non_zero(header.head_entry_realtime);
+        info.last_entry = non_zero(header.tail_entry_realtime);
+        info.first_seqnum = non_zero(header.head_entry_seqnum);
+        info.last_seqnum = non_zero(header.tail_entry_seqnum);
+        info.compression = header.compression().map(String::from);
+        info.compact = header.is_compact();
+
+        info
+    }
+}
+
+fn read_header(path: &Path) -> Result<Header, String> {
+    let mut data = vec![];
+    std::fs::File::open(path)
+        .and_then(|f| f.take(HEADER_READ_SIZE).read_to_end(&mut data))
+        .map_err(|e| e.to_string())?;
+
+    Header::parse(&data).map_err(|e| e.to_string())
+}
+
+/// Journal files of the source, for directories also the ones of the directories
+/// of each machine and namespace within
+pub(crate) fn list_journal_files(source: &JournalSource) -> Vec<JournalFileInfo> {
+    list_files_in(&JOURNAL_DIRS, source)
+}
+
+/// Journal files of the source, the ones of the running system being read from `roots`
+fn list_files_in(roots: &[&str], source: &JournalSource) -> Vec<JournalFileInfo> {
+    let paths: Vec<PathBuf> = match source {
+        JournalSource::Local | JournalSource::Namespace { .. } => namespace_dirs(roots)
+            .iter()
+            .filter(|d| source.includes_namespace(d.namespace.as_deref()))
+            .flat_map(|d| find_journal_files(&d.path))
+            .collect(),
+        JournalSource::Directory { path } => find_journal_files(Path::new(path)),
+        JournalSource::Files { paths } => paths.iter().map(PathBuf::from).collect(),
+        JournalSource::Import { .. } => vec![],
+    };
+
+    let mut files: Vec<JournalFileInfo> = paths.iter().map(|p| JournalFileInfo::read(p)).collect();
+    files.sort_by(|a, b| {
+        (a.first_entry.unwrap_or(u64::MAX), &a.path)
+            .cmp(&(b.first_entry.unwrap_or(u64::MAX), &b.path))
+    });
+
+    files
+}
+
+/// `*.journal` and `*.journal~` files in the directory and its subdirectories
+fn find_journal_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = vec![];
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return files;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+
+        if is_dir {
+            files.extend(find_journal_files(&path));
+        } else if path
+            .extension()
+            .is_some_and(|e| e == "journal" || e == "journal~")
+        {
+            files.push(path);
+        }
+    }
+
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JournalFileState, list_files_in, list_journal_files};
+    use crate::journal::JournalSource;
+
+    #[test]
+    fn files_of_each_machine_directory() {
+        let files = list_journal_files(&JournalSource::Directory {
+            path: "./tests/fixtures/journal".into(),
+        });
+
+        let names: Vec<&str> = files
+            .iter()
+            .map(|f| f.path.rsplit('/').next().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "user-1000@0001-0002.journal",
+                "system.journal",
+                "system.journal",
+                "system@broken.journal~"
+            ]
+        );
+
+        let archived = &files[0];
+        assert_eq!(archived.state, JournalFileState::Archived);
+        assert_eq!(archived.first_seqnum, Some(1));
+        assert_eq!(archived.last_seqnum, Some(1200));
+        assert_eq!(archived.compression.as_deref(), Some("lz4"));
+        assert!(!archived.compact);
+
+        let system = &files[1];
+        assert_eq!(system.state, JournalFileState::Online);
+        assert_eq!(system.error, None);
+        assert_eq!(
+            system.machine_id.as_deref(),
+            Some("5a9c0e4d7b3f4e21a8d6c1f09b2e7d43")
+        );
+        assert_eq!(system.entries, Some(373));
+        assert_eq!(system.first_entry, Some(1_700_007_200_000_000));
+        assert_eq!(system.last_entry, Some(1_700_010_800_000_000));
+        assert_eq!(system.compression.as_deref(), Some("zstd"));
+        assert!(system.compact);
+
+        let broken = &files[3];
+        assert_eq!(broken.state, JournalFileState::Corrupt);
+        assert!(broken.error.is_some());
+        assert_eq!(broken.entries, None);
+    }
+
+    #[test]
+    fn files_of_the_namespaces_read() {
+        let roots = ["./tests/fixtures/journal"];
+        let names = |source: JournalSource| -> Vec<String> {
+            list_files_in(&roots, &source)
+                .into_iter()
+                .map(|f| {
+                    f.path
+                        .trim_start_matches("./tests/fixtures/journal/")
+                        .to_owned()
+                })
+                .collect()
+        };
+
+        let default = "5a9c0e4d7b3f4e21a8d6c1f09b2e7d43";
+        let highvolume = "5a9c0e4d7b3f4e21a8d6c1f09b2e7d43.highvolume";
+
+        assert_eq!(
+            names(JournalSource::Local),
+            vec![
+                format!("{}/user-1000@0001-0002.journal", default),
+                format!("{}/system.journal", default),
+                format!("{}/system@broken.journal~", default),
+            ]
+        );
+        assert_eq!(
+            names(JournalSource::Namespace {
+                namespace: "highvolume".into()
+            }),
+            vec![format!("{}/system.journal", highvolume)]
+        );
+        assert_eq!(
+            names(JournalSource::Namespace {
+                namespace: "+highvolume".into()
+            })
+            .len(),
+            4
+        );
+        assert_eq!(
+            names(JournalSource::Namespace {
+                namespace: "*".into()
+            })
+            .len(),
+            4
+        );
+        assert!(
+            names(JournalSource::Namespace {
+                namespace: "other".into()
+            })
+            .is_empty()
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use super::JournalEntries;
+use serde::Serialize;
+
+/// Message sent while a query is streamed, rows come in the order of the query
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "event", content = "data", rename_all = "camelCase")]
+pub enum QueryEvent {
+    Rows(JournalEntries),
+    Progress(QueryProgress),
+    /// Last message of the query, also sent when it was cancelled
+    Finished(QueryProgress),
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryProgress {
+    /// Entries read from the journal, including the ones left out by the quick search
+    /// and the post filters, which are checked after reading them
+    pub scanned: u64,
+    pub matched: u64,
+    /// Time of the last entry read in microseconds since the epoch
+    pub timestamp: u64,
+    pub cancelled: bool,
+}
+
+/// What the scan of the journal reports to the caller
+pub(crate) enum Scanned<'a> {
+    Row(Vec<String>),
+    Progress(&'a QueryProgress),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QueryEvent;
+    use crate::journal::testing::{self, entry};
+    use crate::journal::{Journal, MemoryJournal, QueryBuilder, Search, SearchMode};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Entries with a message ending in 000 every thousand
+    fn journal(count: u64) -> Journal<MemoryJournal> {
+        testing::journal(
+            (1..=count)
+                .map(|i| {
+                    entry(
+                        i,
+                        &[
+                            ("MESSAGE", &format!("message {}", i)),
+                            ("PRIORITY", "6"),
+                            ("_TRANSPORT", "journal"),
+                        ],
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    fn stream(
+        j: &Journal<MemoryJournal>,
+        qb: &mut QueryBuilder,
+        cancel: &AtomicBool,
+    ) -> Vec<QueryEvent> {
+        let q = qb.with_fields(vec!["MESSAGE".into()]).with_limit(0).build();
+        let mut events = vec![];
+        j.stream_logs(&q, cancel, |e| events.push(e)).unwrap();
+
+        events
+    }
+
+    fn chunk_sizes(events: &[QueryEvent]) -> Vec<usize> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                QueryEvent::Rows(chunk) => Some(chunk.rows.len()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rows_in_chunks() {
+        let j = journal(450);
+        let mut qb = QueryBuilder::default();
+        qb.with_priority_above_or_equal_to(7);
+
+        let events = stream(&j, &mut qb, &AtomicBool::new(false));
+        assert_eq!(chunk_sizes(&events), vec![200, 200, 50]);
+
+        let QueryEvent::Rows(first) = &events[0] else {
+            panic!("expected rows first");
+        };
+        assert_eq!(first.headers, vec!["MESSAGE", "__CURSOR"]);
+        assert_eq!(first.rows[0][0], "message 450");
+
+        let Some(QueryEvent::Finished(progress)) = events.last() else {
+            panic!("expected finished last");
+        };
+        assert_eq!(progress.scanned, 450);
+        assert_eq!(progress.matched, 450);
+        assert!(!progress.cancelled);
+    }
+
+    #[test]
+    fn progress_flushes_rows_of_narrow_queries() {
+        let j = journal(25_000);
+        let mut qb = QueryBuilder::default();
+        qb.with_priority_above_or_equal_to(7)
+            .with_search(Search::parse("000$", SearchMode::Regex).unwrap());
+
+        let events = stream(&j, &mut qb, &AtomicBool::new(false));
+
+        let progress: Vec<(u64, u64, u64)> = events
+            .iter()
+            .filter_map(|e| match e {
+                QueryEvent::Progress(p) => Some((p.scanned, p.matched, p.timestamp)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(progress, vec![(10_000, 10, 15_001), (20_000, 20, 5_001)]);
+
+        // Each progress report sends the rows found so far
+        assert_eq!(chunk_sizes(&events), vec![10, 10, 5]);
+    }
+
+    #[test]
+    fn cancels_promptly() {
+        let j = journal(25_000);
+        let mut qb = QueryBuilder::default();
+        qb.with_priority_above_or_equal_to(7)
+            .with_search(Search::parse("000$", SearchMode::Regex).unwrap());
+        let q = qb.build();
+
+        let cancel = AtomicBool::new(false);
+        let mut finished = None;
+        j.stream_logs(&q, &cancel, |e| match e {
+            QueryEvent::Progress(_) => cancel.store(true, Ordering::Relaxed),
+            QueryEvent::Finished(progress) => finished = Some(progress),
+            QueryEvent::Rows(_) => {}
+        })
+        .unwrap();
+
+        let progress = finished.unwrap();
+        assert!(progress.cancelled);
+        assert_eq!(progress.scanned, 10_000);
+    }
+}
@@ -0,0 +1,21 @@
+//! Entries of the in-memory journals the tests query
+
+use super::{ImportedEntry, Journal, MemoryJournal};
+
+/// Entry logged at `realtime`, with the cursor `c<realtime>`
+pub(crate) fn entry(realtime: u64, fields: &[(&str, &str)]) -> ImportedEntry {
+    ImportedEntry {
+        realtime,
+        monotonic: 0,
+        cursor: format!("c{}", realtime),
+        fields: fields
+            .iter()
+            .map(|(f, v)| (f.to_string(), v.to_string()))
+            .collect(),
+    }
+}
+
+/// Query engine over the entries, given in any order
+pub(crate) fn journal(entries: Vec<ImportedEntry>) -> Journal<MemoryJournal> {
+    Journal::with_backend(MemoryJournal::new(entries))
+}
@@ -1,8 +1,383 @@
+use super::journal_fields;
 use serde::{Deserialize, Serialize};
+use std::process::Command;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Unit {
     pub unit_file: String,
     pub state: String,
-    pub preset: Option<String>
-}
\ No newline at end of file
+    pub preset: Option<String>,
+    #[serde(default)]
+    pub unit_type: UnitType,
+    /// Unit of the service manager of the user, `systemctl --user`
+    #[serde(default)]
+    pub user: bool,
+}
+
+/// Kinds of units listed, the ones running processes that log
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UnitType {
+    #[default]
+    Service,
+    Scope,
+    Timer,
+    Socket,
+    Mount,
+}
+
+impl UnitType {
+    pub const ALL: [UnitType; 5] = [
+        UnitType::Service,
+        UnitType::Scope,
+        UnitType::Timer,
+        UnitType::Socket,
+        UnitType::Mount,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            UnitType::Service => "service",
+            UnitType::Scope => "scope",
+            UnitType::Timer => "timer",
+            UnitType::Socket => "socket",
+            UnitType::Mount => "mount",
+        }
+    }
+
+    /// Type of a unit from the suffix of its name
+    pub fn of(unit: &str) -> Option<UnitType> {
+        let (_, suffix) = unit.rsplit_once('.')?;
+        UnitType::ALL.into_iter().find(|t| t.name() == suffix)
+    }
+}
+
+impl Unit {
+    /// Unit found in the journal, without a unit file to read its state from
+    pub(crate) fn logged(unit_file: &str, user: bool) -> Option<Unit> {
+        Some(Unit {
+            unit_type: UnitType::of(unit_file)?,
+            unit_file: unit_file.to_owned(),
+            state: String::new(),
+            preset: None,
+            user,
+        })
+    }
+}
+
+/// Fields the matches of system units look at, in which glob patterns are expanded
+pub(crate) const UNIT_FIELDS: [&str; 4] = [
+    journal_fields::SYSTEMD_UNIT,
+    journal_fields::COREDUMP_UNIT,
+    journal_fields::UNIT_FILTER,
+    journal_fields::OBJECT_SYSTEMD_UNIT,
+];
+
+/// Fields the matches of user units look at, in which glob patterns are expanded
+pub(crate) const USER_UNIT_FIELDS: [&str; 4] = [
+    journal_fields::SYSTEMD_USER_UNIT,
+    journal_fields::COREDUMP_USER_UNIT,
+    journal_fields::USER_UNIT,
+    journal_fields::OBJECT_SYSTEMD_USER_UNIT,
+];
+
+/// Unit files of the system or of the user service manager. The user manager is
+/// not always reachable, e.g. when running as root without a login session.
+pub(crate) fn list_unit_files(user: bool) -> Vec<Unit> {
+    let types: Vec<&str> = UnitType::ALL.iter().map(|t| t.name()).collect();
+
+    let mut command = Command::new("systemctl");
+    if user {
+        command.arg("--user");
+    }
+    let output = match command
+        .arg("list-unit-files")
+        .arg(format!("--type={}", types.join(",")))
+        .arg("-o")
+        .arg("json")
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => {
+            warn!("Could not run systemctl to list the unit files: {}", e);
+            return vec![];
+        }
+    };
+
+    if !output.status.success() {
+        warn!(
+            "Could not list the unit files: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return vec![];
+    }
+
+    let mut units: Vec<Unit> = match serde_json::from_slice(&output.stdout) {
+        Ok(units) => units,
+        Err(e) => {
+            warn!("Could not parse the unit files: {}", e);
+            return vec![];
+        }
+    };
+
+    units.retain_mut(|u| match UnitType::of(&u.unit_file) {
+        Some(unit_type) => {
+            u.unit_type = unit_type;
+            u.user = user;
+            true
+        }
+        None => false,
+    });
+
+    units
+}
+
+/// Matches for the entries of a unit, the same set `journalctl -u` builds:
+/// the unit's own output, what systemd and privileged daemons log about it,
+/// its coredumps and for slices the output of the units within
+pub(crate) fn unit_matches(unit: &str) -> Vec<Vec<String>> {
+    let mut matches = vec![
+        vec![format!("{}={}", journal_fields::SYSTEMD_UNIT, unit)],
+        vec![
+            format!(
+                "{}={}",
+                journal_fields::MESSAGE_ID,
+                journal_fields::COREDUMP_MESSAGE_ID
+            ),
+            format!("{}=0", journal_fields::UID),
+            format!("{}={}", journal_fields::COREDUMP_UNIT, unit),
+        ],
+        vec![
+            format!("{}=1", journal_fields::PID),
+            format!("{}={}", journal_fields::UNIT_FILTER, unit),
+        ],
+        vec![
+            format!("{}=0", journal_fields::UID),
+            format!("{}={}", journal_fields::OBJECT_SYSTEMD_UNIT, unit),
+        ],
+    ];
+
+    if unit.ends_with(".slice") {
+        matches.push(vec![format!("{}={}", journal_fields::SYSTEMD_SLICE, unit)]);
+    }
+
+    matches
+}
+
+/// Matches for the entries of a user unit of the user with the uid,
+/// the same set `journalctl --user-unit` builds
+pub(crate) fn user_unit_matches(unit: &str, uid: u32) -> Vec<Vec<String>> {
+    let uid_match = format!("{}={}", journal_fields::UID, uid);
+    let root_match = format!("{}=0", journal_fields::UID);
+
+    let mut matches = vec![
+        vec![
+            format!("{}={}", journal_fields::SYSTEMD_USER_UNIT, unit),
+            uid_match.clone(),
+        ],
+        vec![
+            format!("{}={}", journal_fields::USER_UNIT, unit),
+            uid_match.clone(),
+        ],
+        // Matches of the same field are ORed, logged by the user or by root
+        vec![
+            format!("{}={}", journal_fields::COREDUMP_USER_UNIT, unit),
+            uid_match.clone(),
+            root_match.clone(),
+        ],
+        vec![
+            format!("{}={}", journal_fields::OBJECT_SYSTEMD_USER_UNIT, unit),
+            uid_match.clone(),
+            root_match,
+        ],
+    ];
+
+    if unit.ends_with(".slice") {
+        matches.push(vec![
+            format!("{}={}", journal_fields::SYSTEMD_USER_SLICE, unit),
+            uid_match,
+        ]);
+    }
+
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::journal::testing::{self, entry};
+    use crate::journal::{INIT_UNIT, Journal, MemoryJournal, QueryBuilder, UnitType};
+
+    fn journal() -> Journal<MemoryJournal> {
+        let entries: Vec<(&str, Vec<(&str, &str)>)> = vec![
+            (
+                "own output",
+                vec![("_SYSTEMD_UNIT", "nginx.service"), ("_UID", "33")],
+            ),
+            (
+                "started by systemd",
+                vec![("UNIT", "nginx.service"), ("_PID", "1"), ("_UID", "0")],
+            ),
+            (
+                "spoofed unit",
+                vec![
+                    ("UNIT", "nginx.service"),
+                    ("_PID", "4242"),
+                    ("_UID", "1000"),
+                ],
+            ),
+            (
+                "about the unit",
+                vec![("OBJECT_SYSTEMD_UNIT", "nginx.service"), ("_UID", "0")],
+            ),
+            (
+                "coredump",
+                vec![
+                    ("MESSAGE_ID", "fc2e22bc6ee647b6b90729ab34a250b1"),
+                    ("COREDUMP_UNIT", "nginx.service"),
+                    ("_UID", "0"),
+                ],
+            ),
+            (
+                "container",
+                vec![
+                    ("_SYSTEMD_UNIT", "docker-3f2a.scope"),
+                    ("_SYSTEMD_SLICE", "machine.slice"),
+                ],
+            ),
+            (
+                "other container",
+                vec![
+                    ("_SYSTEMD_UNIT", "docker-9b1c.scope"),
+                    ("_SYSTEMD_SLICE", "machine.slice"),
+                ],
+            ),
+            (
+                "user service",
+                vec![("_SYSTEMD_USER_UNIT", "pipewire.service"), ("_UID", "1000")],
+            ),
+            (
+                "user service of another user",
+                vec![("_SYSTEMD_USER_UNIT", "pipewire.service"), ("_UID", "1001")],
+            ),
+            (
+                "started by the user manager",
+                vec![("USER_UNIT", "pipewire.service"), ("_UID", "1000")],
+            ),
+            (
+                "user coredump",
+                vec![("COREDUMP_USER_UNIT", "pipewire.service"), ("_UID", "0")],
+            ),
+            (
+                "user timer",
+                vec![("_SYSTEMD_USER_UNIT", "backup.timer"), ("_UID", "1000")],
+            ),
+        ];
+
+        testing::journal(
+            entries
+                .into_iter()
+                .enumerate()
+                .map(|(i, (message, mut fields))| {
+                    fields.extend([
+                        ("MESSAGE", message),
+                        ("PRIORITY", "6"),
+                        ("_TRANSPORT", "journal"),
+                    ]);
+                    entry(i as u64 + 1, &fields)
+                })
+                .collect(),
+        )
+    }
+
+    fn names(units: &[&str]) -> Vec<String> {
+        units.iter().map(|u| u.to_string()).collect()
+    }
+
+    fn messages(units: &[&str]) -> Vec<String> {
+        read(QueryBuilder::default().with_units(names(units)))
+    }
+
+    fn user_messages(units: &[&str], uid: u32) -> Vec<String> {
+        read(QueryBuilder::default().with_user_units(names(units), uid))
+    }
+
+    fn read(qb: &mut QueryBuilder) -> Vec<String> {
+        let q = qb
+            .with_fields(vec!["MESSAGE".into()])
+            .with_priority_above_or_equal_to(7)
+            .build();
+
+        journal()
+            .query_logs(&q)
+            .unwrap()
+            .rows
+            .into_iter()
+            .map(|r| r[0].clone())
+            .collect()
+    }
+
+    #[test]
+    fn matches_like_journalctl() {
+        assert_eq!(
+            messages(&["nginx.service"]),
+            vec![
+                "coredump",
+                "about the unit",
+                "started by systemd",
+                "own output"
+            ]
+        );
+    }
+
+    #[test]
+    fn slices_and_patterns() {
+        assert_eq!(
+            messages(&["machine.slice"]),
+            vec!["other container", "container"]
+        );
+        assert_eq!(
+            messages(&["docker-*.scope"]),
+            vec!["other container", "container"]
+        );
+        assert_eq!(
+            messages(&["docker-3*.scope", "nginx.service"]),
+            vec![
+                "container",
+                "coredump",
+                "about the unit",
+                "started by systemd",
+                "own output"
+            ]
+        );
+        assert!(messages(&["podman-*.scope"]).is_empty());
+    }
+
+    #[test]
+    fn user_units_of_the_user() {
+        assert_eq!(
+            user_messages(&["pipewire.service"], 1000),
+            vec![
+                "user coredump",
+                "started by the user manager",
+                "user service"
+            ]
+        );
+        assert_eq!(
+            user_messages(&["pipewire.service"], 1001),
+            vec!["user coredump", "user service of another user"]
+        );
+        assert_eq!(user_messages(&["*.timer"], 1000), vec!["user timer"]);
+
+        // User units are not system units
+        assert!(messages(&["pipewire.service"]).is_empty());
+    }
+
+    #[test]
+    fn unit_types() {
+        assert_eq!(UnitType::of("sshd.service"), Some(UnitType::Service));
+        assert_eq!(UnitType::of("session-2.scope"), Some(UnitType::Scope));
+        assert_eq!(UnitType::of("home.mount"), Some(UnitType::Mount));
+        assert_eq!(UnitType::of("user.slice"), None);
+        assert_eq!(UnitType::of(INIT_UNIT), None);
+    }
+}
@@ -1,58 +1,383 @@
+use crate::journal::AuditEvent;
 use crate::journal::Boot;
+use crate::journal::CatalogEntry;
+use crate::journal::Coredump;
+use crate::journal::Exclusions;
+use crate::journal::FieldInfo;
+use crate::journal::FilterExpression;
+use crate::journal::Histogram;
 use crate::journal::JournalError;
+use crate::journal::KernelFilter;
+use crate::journal::QueryBuilder;
+use crate::journal::StorageReport;
 use crate::journal::Unit;
-use crate::journal::{INIT_UNIT, QueryBuilder};
-use crate::journal::{Journal, OpenFlags};
+use crate::journal::{Anchor, JournalPage};
+use crate::journal::{Follower, Query};
+use crate::journal::{Journal, JournalSource};
 use crate::journal::{JournalEntries, JournalEntry};
+use crate::journal::{QueryEvent, QueryProgress};
+use crate::journal::{Search, SearchMode};
+use crate::journal::{SessionId, Sessions};
 use chrono::{DateTime, Duration, Utc};
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tauri::async_runtime::Mutex;
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Emitter, Manager};
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct JournalQuery {
     fields: Vec<String>,
     priority: u32,
+    #[serde(default)]
     limit: u64,
     quick_search: String,
     reset_position: bool,
     services: Vec<String>,
+    /// Units of the user's service manager, `systemctl --user`
+    #[serde(default)]
+    user_services: Vec<String>,
     transports: Vec<String>,
     datetime_from: String,
     datetime_to: String,
     boot_ids: Vec<String>,
+    /// Journal namespaces, among the ones of the source
+    #[serde(default)]
+    namespaces: Vec<String>,
+    /// The default namespace along with `namespaces`, or on its own when they are empty
+    #[serde(default)]
+    default_namespace: bool,
+    #[serde(default)]
+    cursor: String,
+    #[serde(default)]
+    filter: String,
+    #[serde(default)]
+    search_mode: SearchMode,
+    /// Exact set of priorities, overrides `priority` when not empty
+    #[serde(default)]
+    priorities: Vec<u32>,
+    #[serde(default)]
+    excluded_units: Vec<String>,
+    #[serde(default)]
+    excluded_identifiers: Vec<String>,
+    /// Regular expressions of the messages to hide
+    #[serde(default)]
+    excluded_messages: Vec<String>,
+    /// Kernel messages of the current boot instead of the transports, like `journalctl -k -b`
+    #[serde(default)]
+    kernel: Option<KernelFilter>,
+}
+
+impl JournalQuery {
+    pub(crate) fn fields(&self) -> &[String] {
+        &self.fields
+    }
+}
+
+/// Event emitted with the new entries while following the journal
+const FOLLOW_EVENT: &str = "journal-follow";
+
+/// Journal handles of the views, each one with its own read position
+pub(crate) type JournalSessions = Mutex<Sessions<Mutex<Journal>>>;
+
+/// How long a session can go unused before its journal is closed
+pub(crate) const SESSION_IDLE_TIMEOUT: std::time::Duration =
+    std::time::Duration::from_secs(30 * 60);
+
+/// Opens a journal on the current source for a view, to be passed along with its queries.
+/// With `all_namespaces` every namespace of the running system is read, for views selecting
+/// namespaces in their queries.
+#[tauri::command]
+#[instrument]
+pub(crate) async fn open_session(
+    all_namespaces: Option<bool>,
+    sessions: tauri::State<'_, JournalSessions>,
+    source: tauri::State<'_, Mutex<JournalSource>>,
+) -> Result<SessionId, JournalError> {
+    let mut source = source.lock().await.clone();
+    if all_namespaces.unwrap_or(false) {
+        source = source.with_all_namespaces();
+    }
+
+    let j = open_source(source).await?;
+    let id = sessions.lock().await.open(Mutex::new(j));
+    debug!("Opened session {}", id);
+
+    Ok(id)
+}
+
+#[tauri::command]
+#[instrument]
+pub(crate) async fn close_session(
+    session_id: SessionId,
+    sessions: tauri::State<'_, JournalSessions>,
+) -> Result<(), JournalError> {
+    if !sessions.lock().await.close(session_id) {
+        debug!("Session {} was already closed", session_id);
+    }
+
+    Ok(())
+}
+
+/// Source to read for the query, with every namespace when it selects some
+pub(crate) fn query_source(source: &JournalSource, q: &Query) -> JournalSource {
+    if q.namespaces.is_empty() {
+        source.clone()
+    } else {
+        source.with_all_namespaces()
+    }
+}
+
+/// Opens a journal on the source on a blocking worker, as imported dumps are parsed whole
+async fn open_source(source: JournalSource) -> Result<Journal, JournalError> {
+    tauri::async_runtime::spawn_blocking(move || Journal::open_source(&source))
+        .await
+        .map_err(std::io::Error::other)?
+}
+
+async fn session_journal(
+    sessions: &JournalSessions,
+    session_id: SessionId,
+) -> Result<Arc<Mutex<Journal>>, JournalError> {
+    sessions.lock().await.get(session_id)
 }
 
 #[tauri::command]
 #[instrument]
 pub(crate) async fn get_logs(
-    mut query: JournalQuery,
-    journal: tauri::State<'_, Mutex<Journal>>,
+    query: JournalQuery,
+    session_id: SessionId,
+    sessions: tauri::State<'_, JournalSessions>,
 ) -> Result<JournalEntries, JournalError> {
     debug!("Getting logs...");
 
-    // If systemd service is specified, remove from unit filter
-    // and add it back later as pid filter
-    let mut add_init_filter = false;
-    if !query.services.is_empty() && query.services[0] == INIT_UNIT {
-        query.services.remove(0);
-        add_init_filter = true;
+    let q = build_query(query)?;
+
+    let journal = session_journal(&sessions, session_id).await?;
+    let lock = journal.lock().await;
+    let logs = lock.query_logs(&q)?;
+    debug!("Found {} entries.", logs.rows.len());
+
+    Ok(logs)
+}
+
+/// Cancellation flags of the queries being streamed
+#[derive(Debug, Default)]
+pub(crate) struct RunningQueries {
+    next_id: u64,
+    cancel_flags: HashMap<u64, Arc<AtomicBool>>,
+}
+
+impl RunningQueries {
+    fn start(&mut self) -> (u64, Arc<AtomicBool>) {
+        self.next_id += 1;
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.cancel_flags.insert(self.next_id, cancel.clone());
+
+        (self.next_id, cancel)
+    }
+
+    fn finish(&mut self, id: u64) {
+        self.cancel_flags.remove(&id);
+    }
+
+    fn cancel(&self, id: u64) -> bool {
+        match self.cancel_flags.get(&id) {
+            Some(cancel) => {
+                cancel.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Runs the query on a worker thread, sending the rows and the progress through the channel.
+/// Returns the id to cancel the query with, the channel receives `Finished` in any case.
+#[tauri::command]
+#[instrument(skip(app, on_event))]
+pub(crate) async fn stream_logs(
+    query: JournalQuery,
+    session_id: SessionId,
+    on_event: Channel<QueryEvent>,
+    app: AppHandle,
+    sessions: tauri::State<'_, JournalSessions>,
+    running: tauri::State<'_, Mutex<RunningQueries>>,
+) -> Result<u64, JournalError> {
+    debug!("Streaming logs...");
+
+    let q = build_query(query)?;
+    let journal = session_journal(&sessions, session_id).await?;
+    let (query_id, cancel) = running.lock().await.start();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = journal.blocking_lock().stream_logs(&q, &cancel, |event| {
+            // The view is gone, no point in reading any further
+            if on_event.send(event).is_err() {
+                cancel.store(true, Ordering::Relaxed);
+            }
+        });
+
+        if let Err(e) = result {
+            error!("Streaming query {} failed: {}", query_id, e);
+            let finished = QueryProgress {
+                cancelled: true,
+                ..QueryProgress::default()
+            };
+            if on_event.send(QueryEvent::Finished(finished)).is_err() {
+                warn!("Could not report the failure of query {}", query_id);
+            }
+        }
+
+        app.state::<Mutex<RunningQueries>>()
+            .blocking_lock()
+            .finish(query_id);
+        debug!("Finished streaming query {}", query_id);
+    });
+
+    Ok(query_id)
+}
+
+#[tauri::command]
+#[instrument]
+pub(crate) async fn cancel_query(
+    query_id: u64,
+    running: tauri::State<'_, Mutex<RunningQueries>>,
+) -> Result<(), JournalError> {
+    if !running.lock().await.cancel(query_id) {
+        debug!("Query {} already finished", query_id);
+    }
+
+    Ok(())
+}
+
+/// Largest number of entries on either side of the anchor of a page
+const MAX_PAGE_SIDE: u64 = 10_000;
+
+#[tauri::command]
+#[instrument]
+pub(crate) async fn get_logs_around(
+    query: JournalQuery,
+    anchor: Anchor,
+    before: u64,
+    after: u64,
+    session_id: SessionId,
+    sessions: tauri::State<'_, JournalSessions>,
+) -> Result<JournalPage, JournalError> {
+    debug!("Getting logs around {:?}...", anchor);
+
+    if before > MAX_PAGE_SIDE || after > MAX_PAGE_SIDE {
+        return Err(JournalError::InvalidQuery(format!(
+            "a page can't have more than {} entries on either side",
+            MAX_PAGE_SIDE
+        )));
+    }
+
+    let q = build_query(query)?;
+
+    let journal = session_journal(&sessions, session_id).await?;
+    let lock = journal.lock().await;
+    let page = lock.get_page(&q, &anchor, before, after)?;
+    debug!("Found {} entries.", page.rows.len());
+
+    Ok(page)
+}
+
+#[tauri::command]
+#[instrument(skip(app))]
+pub(crate) async fn start_follow(
+    query: JournalQuery,
+    app: AppHandle,
+    follower: tauri::State<'_, Mutex<Option<Follower>>>,
+    source: tauri::State<'_, Mutex<JournalSource>>,
+) -> Result<(), JournalError> {
+    debug!("Starting to follow the journal...");
+
+    let q = build_query(query)?;
+
+    // Own handle rather than a session one, as the follow thread keeps it until stopped
+    let j = open_source(query_source(&*source.lock().await, &q)).await?;
+    if j.backend().is_in_memory() {
+        return Err(JournalError::InvalidQuery(
+            "the journal was read into memory, it doesn't change and can't be followed".into(),
+        ));
     }
 
+    let next = Follower::start(j, q, move |entries| {
+        if let Err(e) = app.emit(FOLLOW_EVENT, entries) {
+            warn!("Could not emit followed entries: {}", e);
+        }
+    });
+
+    // Only one follow session at a time, replace any previous one
+    let previous = follower.lock().await.replace(next);
+    join_follower(previous).await;
+
+    Ok(())
+}
+
+/// Stops a follower, waiting for its thread on a blocking worker once the lock is released
+async fn join_follower(follower: Option<Follower>) {
+    if let Some(f) = follower {
+        f.request_stop();
+
+        if tauri::async_runtime::spawn_blocking(move || f.stop())
+            .await
+            .is_err()
+        {
+            error!("Could not wait for the follow thread");
+        }
+    }
+}
+
+#[tauri::command]
+#[instrument]
+pub(crate) async fn stop_follow(
+    follower: tauri::State<'_, Mutex<Option<Follower>>>,
+) -> Result<(), JournalError> {
+    debug!("Stopping to follow the journal...");
+
+    let previous = follower.lock().await.take();
+    join_follower(previous).await;
+
+    Ok(())
+}
+
+pub(crate) fn build_query(query: JournalQuery) -> Result<Query, JournalError> {
     let mut qb = QueryBuilder::default();
     let q = qb
         .with_fields(query.fields)
         .with_limit(query.limit)
-        .with_quick_search(query.quick_search)
         .reset_position(query.reset_position)
+        .with_cursor(query.cursor)
         .with_priority_above_or_equal_to(query.priority)
-        .with_units(query.services)
+        .with_priorities(query.priorities)
         .with_transports(query.transports)
-        .with_boot_ids(query.boot_ids);
+        .with_boot_ids(query.boot_ids)
+        .with_namespaces(query.namespaces)
+        .with_default_namespace(query.default_namespace)
+        .with_exclusions(Exclusions::new(
+            query.excluded_units,
+            query.excluded_identifiers,
+            &query.excluded_messages,
+        )?);
 
-    // Add back filter for systemd service as pid=1
-    if add_init_filter {
-        q.with_pid(1);
+    q.with_units(query.services)
+        .with_user_units(query.user_services, current_uid());
+
+    if let Some(kernel) = query.kernel {
+        q.with_kernel(kernel);
+    }
+
+    if !query.quick_search.is_empty() {
+        q.with_search(Search::parse(&query.quick_search, query.search_mode)?);
+    }
+
+    if !query.filter.trim().is_empty() {
+        q.with_filter(FilterExpression::parse(&query.filter)?);
     }
 
     let date_from = DateTime::parse_from_rfc3339(&query.datetime_from).ok();
@@ -69,73 +394,137 @@ pub(crate) async fn get_logs(
         q.with_date_less_than(datetime_to.timestamp_micros() as u64);
     }
 
-    let q = q.build();
-
-    let lock = journal.lock().await;
-    let logs = lock.query_logs(&q)?;
-    debug!("Found {} entries.", logs.rows.len());
-
-    Ok(logs)
+    Ok(q.build())
 }
 
 #[tauri::command]
 #[instrument]
-pub(crate) async fn get_full_entry(timestamp: u64) -> Result<JournalEntry, JournalError> {
-    debug!("Getting full entry for timestamp {}...", timestamp);
+pub(crate) async fn get_full_entry(
+    cursor: String,
+    session_id: SessionId,
+    sessions: tauri::State<'_, JournalSessions>,
+) -> Result<JournalEntry, JournalError> {
+    debug!("Getting full entry for cursor {}...", cursor);
 
-    let j = Journal::open(
-        OpenFlags::SD_JOURNAL_LOCAL_ONLY
-            | OpenFlags::SD_JOURNAL_SYSTEM
-            | OpenFlags::SD_JOURNAL_CURRENT_USER,
-    )
-    .unwrap();
+    let journal = session_journal(&sessions, session_id).await?;
+    let entry = journal.lock().await.get_full_entry(&cursor)?;
 
-    let entry = j.get_full_entry(timestamp)?;
-
-    debug!("Found entry for timestamp {}", timestamp);
+    debug!("Found entry for cursor {}", cursor);
 
     Ok(entry)
 }
 
+/// User units are the ones of the user running the viewer, like with journalctl
+fn current_uid() -> u32 {
+    // SAFETY: getuid always succeeds
+    unsafe { libc::getuid() }
+}
+
+/// Largest number of buckets a histogram can be split into
+const MAX_HISTOGRAM_BUCKETS: u64 = 10_000;
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct SummaryQuery {
+pub struct HistogramQuery {
     priority: u32,
+    #[serde(default)]
+    services: Vec<String>,
+    #[serde(default)]
+    user_services: Vec<String>,
+    #[serde(default)]
+    transports: Vec<String>,
+    #[serde(default)]
+    boot_ids: Vec<String>,
+    #[serde(default)]
+    datetime_from: String,
+    #[serde(default)]
+    datetime_to: String,
+    bucket_width_seconds: u64,
+}
+
+/// Start, end and bucket width of the histogram in microseconds, the last 5 days by default
+fn histogram_range(query: &HistogramQuery) -> Result<(u64, u64, u64), JournalError> {
+    let datetime_from = DateTime::parse_from_rfc3339(&query.datetime_from)
+        .map(|d| d.to_utc())
+        .unwrap_or_else(|_| Utc::now() - Duration::days(5));
+    let datetime_to = DateTime::parse_from_rfc3339(&query.datetime_to)
+        .map(|d| d.to_utc())
+        .unwrap_or_else(|_| Utc::now());
+
+    let (Ok(from), Ok(to)) = (
+        u64::try_from(datetime_from.timestamp_micros()),
+        u64::try_from(datetime_to.timestamp_micros()),
+    ) else {
+        return Err(JournalError::InvalidQuery(
+            "the range can't start before the epoch".into(),
+        ));
+    };
+
+    let Some(bucket_width) = query.bucket_width_seconds.checked_mul(1_000_000) else {
+        return Err(JournalError::InvalidQuery(
+            "the bucket width is too large".into(),
+        ));
+    };
+
+    if bucket_width == 0 || to <= from {
+        return Err(JournalError::InvalidQuery(
+            "the range and bucket width must be positive".into(),
+        ));
+    }
+
+    if (to - from) / bucket_width > MAX_HISTOGRAM_BUCKETS {
+        return Err(JournalError::InvalidQuery(format!(
+            "the range can't be split in more than {} buckets",
+            MAX_HISTOGRAM_BUCKETS
+        )));
+    }
+
+    Ok((from, to, bucket_width))
 }
 
 #[tauri::command]
 #[instrument]
-pub(crate) async fn get_summary(query: SummaryQuery) -> Result<JournalEntries, JournalError> {
-    debug!("Getting summary...");
-    let j = Journal::open(
-        OpenFlags::SD_JOURNAL_LOCAL_ONLY
-            | OpenFlags::SD_JOURNAL_SYSTEM
-            | OpenFlags::SD_JOURNAL_CURRENT_USER,
-    )
-    .unwrap();
+pub(crate) async fn get_histogram(
+    query: HistogramQuery,
+    session_id: SessionId,
+    sessions: tauri::State<'_, JournalSessions>,
+) -> Result<Histogram, JournalError> {
+    debug!("Getting histogram...");
+
+    let (from, to, bucket_width) = histogram_range(&query)?;
 
-    let datetime_from = Utc::now() - Duration::days(5);
-    let datetime_to = Utc::now() + Duration::days(1);
     let mut qb = QueryBuilder::default();
     let q = qb
-        .with_fields(vec!["__REALTIME".into()])
-        .with_limit(10_000)
-        .with_date_more_than(datetime_from.timestamp_micros() as u64)
-        .with_date_less_than(datetime_to.timestamp_micros() as u64)
         .with_priority_above_or_equal_to(query.priority)
-        .build();
+        .with_transports(query.transports)
+        .with_boot_ids(query.boot_ids);
 
-    let logs = j.query_logs(&q)?;
-    debug!("Found {} entries.", logs.rows.len());
+    q.with_units(query.services)
+        .with_user_units(query.user_services, current_uid());
 
-    Ok(logs)
+    let q = q.build();
+
+    let journal = session_journal(&sessions, session_id).await?;
+    let histogram = journal
+        .lock()
+        .await
+        .get_histogram(&q, from, to, bucket_width)?;
+    debug!("Counted {} buckets", histogram.buckets.len());
+
+    Ok(histogram)
 }
 
 #[tauri::command]
 #[instrument]
-pub(crate) async fn get_services() -> Result<Vec<Unit>, JournalError> {
+pub(crate) async fn get_services(
+    session_id: SessionId,
+    sessions: tauri::State<'_, JournalSessions>,
+    source: tauri::State<'_, Mutex<JournalSource>>,
+) -> Result<Vec<Unit>, JournalError> {
     debug!("Getting services...");
-    let services = Journal::list_services();
+    let source = source.lock().await.clone();
+    let journal = session_journal(&sessions, session_id).await?;
+    let services = journal.lock().await.list_services(&source)?;
     debug!("found {} services", services.len());
 
     Ok(services)
@@ -143,10 +532,639 @@ pub(crate) async fn get_services() -> Result<Vec<Unit>, JournalError> {
 
 #[tauri::command]
 #[instrument]
-pub(crate) async fn get_boots() -> Result<Vec<Boot>, JournalError> {
+pub(crate) async fn get_boots(
+    session_id: SessionId,
+    sessions: tauri::State<'_, JournalSessions>,
+) -> Result<Vec<Boot>, JournalError> {
     debug!("Getting boots...");
-    let boots = Journal::list_boots();
+    let journal = session_journal(&sessions, session_id).await?;
+    let boots = journal.lock().await.list_boots()?;
     debug!("found {} boots", boots.len());
 
     Ok(boots)
 }
+
+/// Number of entries of a boot, left out of the boot list as it reads the whole boot
+#[tauri::command]
+#[instrument]
+pub(crate) async fn get_boot_entry_count(
+    boot_id: String,
+    session_id: SessionId,
+    sessions: tauri::State<'_, JournalSessions>,
+) -> Result<u64, JournalError> {
+    debug!("Counting the entries of boot {}...", boot_id);
+    let journal = session_journal(&sessions, session_id).await?;
+
+    journal.lock().await.count_boot_entries(&boot_id)
+}
+
+/// Crashes of a boot, or of every boot when none is given
+#[tauri::command]
+#[instrument]
+pub(crate) async fn get_coredumps(
+    boot_id: Option<String>,
+    session_id: SessionId,
+    sessions: tauri::State<'_, JournalSessions>,
+) -> Result<Vec<Coredump>, JournalError> {
+    debug!("Getting coredumps...");
+    let journal = session_journal(&sessions, session_id).await?;
+    let coredumps = journal.lock().await.list_coredumps(boot_id.as_deref())?;
+    debug!("found {} coredumps", coredumps.len());
+
+    Ok(coredumps)
+}
+
+/// Entries of the crashed unit in the minutes before the crash of the cursor
+#[tauri::command]
+#[instrument]
+pub(crate) async fn get_coredump_logs(
+    cursor: String,
+    minutes_before: u64,
+    fields: Vec<String>,
+    session_id: SessionId,
+    sessions: tauri::State<'_, JournalSessions>,
+) -> Result<JournalEntries, JournalError> {
+    debug!("Getting the logs before the crash...");
+    let journal = session_journal(&sessions, session_id).await?;
+    let j = journal.lock().await;
+    let coredump = j.get_coredump(&cursor)?;
+
+    let q = coredump.related_logs_query(minutes_before.saturating_mul(60_000_000), fields);
+    let logs = j.query_logs(&q)?;
+    debug!("Found {} entries.", logs.rows.len());
+
+    Ok(logs)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AuditQuery {
+    boot_ids: Vec<String>,
+    datetime_from: String,
+    datetime_to: String,
+    /// Number of records read, the events are made of one or more of them
+    limit: u64,
+}
+
+impl Default for AuditQuery {
+    fn default() -> Self {
+        AuditQuery {
+            boot_ids: vec![],
+            datetime_from: String::new(),
+            datetime_to: String::new(),
+            limit: 1000,
+        }
+    }
+}
+
+/// Events of the kernel audit subsystem, newest first
+#[tauri::command]
+#[instrument]
+pub(crate) async fn get_audit_events(
+    query: AuditQuery,
+    session_id: SessionId,
+    sessions: tauri::State<'_, JournalSessions>,
+) -> Result<Vec<AuditEvent>, JournalError> {
+    debug!("Getting audit events...");
+
+    let mut qb = QueryBuilder::default();
+    qb.with_audit()
+        .with_limit(query.limit)
+        .with_boot_ids(query.boot_ids);
+
+    if let Ok(x) = DateTime::parse_from_rfc3339(&query.datetime_from) {
+        qb.with_date_more_than(x.timestamp_micros() as u64);
+    }
+    if let Ok(x) = DateTime::parse_from_rfc3339(&query.datetime_to) {
+        qb.with_date_less_than(x.timestamp_micros() as u64);
+    }
+
+    let journal = session_journal(&sessions, session_id).await?;
+    let events = journal.lock().await.list_audit_events(&qb.build())?;
+    debug!("found {} audit events", events.len());
+
+    Ok(events)
+}
+
+/// Disk usage of the journal, its files and the retention limits of journald
+#[tauri::command]
+#[instrument]
+pub(crate) async fn get_storage_report(
+    source: tauri::State<'_, Mutex<JournalSource>>,
+) -> Result<StorageReport, JournalError> {
+    debug!("Getting the storage report...");
+    let report = Journal::storage_report(&*source.lock().await)?;
+    debug!("found {} journal files", report.files.len());
+
+    Ok(report)
+}
+
+/// Journal namespaces of the running system besides the default one, for the namespace filter
+#[tauri::command]
+#[instrument]
+pub(crate) async fn get_namespaces() -> Result<Vec<String>, JournalError> {
+    debug!("Getting the journal namespaces...");
+    let namespaces = Journal::list_namespaces();
+    debug!("found {} namespaces", namespaces.len());
+
+    Ok(namespaces)
+}
+
+/// Message ids explained in the catalog, for browsing it
+#[tauri::command]
+#[instrument]
+pub(crate) async fn get_catalog() -> Result<Vec<CatalogEntry>, JournalError> {
+    debug!("Getting the message catalog...");
+    let catalog = Journal::list_catalog();
+    debug!("found {} catalog entries", catalog.len());
+
+    Ok(catalog)
+}
+
+/// Catalog text of a message id, None if it is not in the catalog
+#[tauri::command]
+#[instrument]
+pub(crate) async fn get_catalog_text(message_id: String) -> Result<Option<String>, JournalError> {
+    debug!("Getting the catalog text of {}...", message_id);
+
+    Journal::get_catalog_text(&message_id)
+}
+
+#[tauri::command]
+#[instrument]
+pub(crate) async fn get_fields(
+    session_id: SessionId,
+    sessions: tauri::State<'_, JournalSessions>,
+) -> Result<Vec<FieldInfo>, JournalError> {
+    debug!("Getting fields...");
+    let journal = session_journal(&sessions, session_id).await?;
+    let fields = journal.lock().await.list_fields()?;
+    debug!("found {} fields", fields.len());
+
+    Ok(fields)
+}
+
+#[tauri::command]
+#[instrument]
+pub(crate) async fn get_field_values(
+    field: String,
+    session_id: SessionId,
+    sessions: tauri::State<'_, JournalSessions>,
+) -> Result<Vec<String>, JournalError> {
+    debug!("Getting values for field {}...", field);
+    let journal = session_journal(&sessions, session_id).await?;
+    let values = journal.lock().await.get_field_values(&field)?;
+    debug!("found {} values", values.len());
+
+    Ok(values)
+}
+
+#[tauri::command]
+#[instrument]
+pub(crate) async fn get_journal_source(
+    source: tauri::State<'_, Mutex<JournalSource>>,
+) -> Result<JournalSource, JournalError> {
+    Ok(source.lock().await.clone())
+}
+
+#[tauri::command]
+#[instrument]
+pub(crate) async fn set_journal_source(
+    new_source: JournalSource,
+    sessions: tauri::State<'_, JournalSessions>,
+    source: tauri::State<'_, Mutex<JournalSource>>,
+    follower: tauri::State<'_, Mutex<Option<Follower>>>,
+) -> Result<(), JournalError> {
+    debug!("Switching journal source to {:?}...", new_source);
+
+    // Open first, so the current source is kept if the new one can't be read
+    open_source(new_source.clone()).await?;
+
+    // Entries being followed belong to the previous source
+    let previous = follower.lock().await.take();
+    join_follower(previous).await;
+
+    // The sessions read the previous source, views open new ones when theirs is gone
+    sessions.lock().await.clear();
+    *source.lock().await = new_source;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        FilterExpression, HistogramQuery, JournalQuery, build_query, histogram_range, query_source,
+    };
+    use crate::journal::JournalError;
+    use crate::journal::testing::{self, entry};
+    use crate::journal::{INIT_UNIT, ImportedEntry, Journal, JournalSource, MemoryJournal};
+    use serde_json::json;
+
+    /// 2024-01-01T00:00:00Z in microseconds
+    const START: u64 = 1_704_067_200_000_000;
+
+    /// Entry the given seconds after the start, with the cursor `c<seconds>`
+    fn at(seconds: u64, fields: &[(&str, &str)]) -> ImportedEntry {
+        ImportedEntry {
+            cursor: format!("c{}", seconds),
+            ..entry(START + seconds * 1_000_000, fields)
+        }
+    }
+
+    fn journal() -> Journal<MemoryJournal> {
+        testing::journal(vec![
+            at(
+                1,
+                &[
+                    ("MESSAGE", "Reached target Basic System"),
+                    ("PRIORITY", "6"),
+                    ("_PID", "1"),
+                    ("_TRANSPORT", "journal"),
+                ],
+            ),
+            at(
+                2,
+                &[
+                    ("MESSAGE", "Server listening on port 22"),
+                    ("PRIORITY", "6"),
+                    ("_PID", "812"),
+                    ("_SYSTEMD_UNIT", "sshd.service"),
+                    ("_TRANSPORT", "syslog"),
+                ],
+            ),
+            at(
+                3,
+                &[
+                    ("MESSAGE", "Failed password for root"),
+                    ("PRIORITY", "4"),
+                    ("_PID", "815"),
+                    ("_SYSTEMD_UNIT", "sshd.service"),
+                    ("_TRANSPORT", "syslog"),
+                ],
+            ),
+            at(
+                4,
+                &[
+                    ("MESSAGE", "Out of memory: Killed process 815"),
+                    ("PRIORITY", "2"),
+                    ("_TRANSPORT", "kernel"),
+                ],
+            ),
+        ])
+    }
+
+    fn query(overrides: serde_json::Value) -> JournalQuery {
+        let mut query = json!({
+            "fields": ["MESSAGE"],
+            "priority": 6,
+            "quickSearch": "",
+            "resetPosition": true,
+            "services": [],
+            "transports": ["journal", "syslog"],
+            "datetimeFrom": "",
+            "datetimeTo": "",
+            "bootIds": [],
+        });
+        query
+            .as_object_mut()
+            .unwrap()
+            .extend(overrides.as_object().unwrap().clone());
+
+        serde_json::from_value(query).unwrap()
+    }
+
+    fn messages(query: JournalQuery) -> Vec<String> {
+        let q = build_query(query).unwrap();
+        journal()
+            .query_logs(&q)
+            .unwrap()
+            .rows
+            .into_iter()
+            .map(|r| r[0].clone())
+            .collect()
+    }
+
+    #[test]
+    fn imported_dump_source() {
+        let source = JournalSource::Import {
+            path: "./tests/fixtures/import/dump.export".into(),
+        };
+        let j = Journal::open_source(&source).unwrap();
+        assert!(j.backend().is_in_memory());
+
+        let messages = |query: JournalQuery| -> Vec<String> {
+            let q = build_query(query).unwrap();
+            j.query_logs(&q)
+                .unwrap()
+                .rows
+                .into_iter()
+                .map(|r| r[0].clone())
+                .collect()
+        };
+        assert_eq!(
+            messages(query(json!({"priority": 7, "bootIds": ["b1"]}))),
+            vec!["disk failure", "Started nginx"]
+        );
+        assert_eq!(
+            messages(query(
+                json!({"priority": 7, "services": ["smartd.service"]})
+            )),
+            vec!["disk failure"]
+        );
+
+        let entry = j.get_full_entry("c3000").unwrap();
+        let i = entry.headers.iter().position(|h| h == "MESSAGE").unwrap();
+        assert_eq!(entry.values[i], "Failed to start sshd");
+
+        let missing = JournalSource::Import {
+            path: "./tests/fixtures/import/missing.export".into(),
+        };
+        assert!(matches!(
+            Journal::open_source(&missing),
+            Err(JournalError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn init_unit_and_unit_patterns() {
+        assert_eq!(
+            messages(query(json!({ "services": [INIT_UNIT] }))),
+            vec!["Reached target Basic System"]
+        );
+
+        // Every unit adds its entries to the others
+        let q = build_query(query(json!({ "services": [INIT_UNIT, "sshd.service"] }))).unwrap();
+        assert_eq!(q.pid, 0);
+        assert_eq!(
+            messages(query(json!({ "services": [INIT_UNIT, "sshd.service"] }))),
+            vec![
+                "Failed password for root",
+                "Server listening on port 22",
+                "Reached target Basic System"
+            ]
+        );
+
+        assert_eq!(
+            messages(query(json!({ "services": ["ssh*.service"] }))),
+            vec!["Failed password for root", "Server listening on port 22"]
+        );
+    }
+
+    #[test]
+    fn services_that_logged() {
+        let source = JournalSource::Directory {
+            path: "/var/log/journal".into(),
+        };
+        let units: Vec<String> = journal()
+            .list_services(&source)
+            .unwrap()
+            .into_iter()
+            .map(|u| u.unit_file)
+            .collect();
+
+        assert_eq!(units, vec![INIT_UNIT, "sshd.service"]);
+    }
+
+    #[test]
+    fn priority_transports_and_limit() {
+        assert_eq!(
+            messages(query(json!({ "priority": 4 }))),
+            vec!["Failed password for root"]
+        );
+
+        assert_eq!(
+            messages(query(json!({ "priority": 7, "transports": ["kernel"] }))),
+            vec!["Out of memory: Killed process 815"]
+        );
+
+        assert_eq!(
+            messages(query(json!({ "limit": 2 }))),
+            vec!["Failed password for root", "Server listening on port 22"]
+        );
+    }
+
+    #[test]
+    fn kernel_view() {
+        assert_eq!(
+            messages(query(json!({ "kernel": {} }))),
+            vec!["Out of memory: Killed process 815"]
+        );
+
+        assert!(messages(query(json!({ "kernel": { "subsystems": ["usb"] } }))).is_empty());
+    }
+
+    #[test]
+    fn namespaces() {
+        // Opened with all namespaces, the default one has no _NAMESPACE field
+        let j = testing::journal(vec![
+            at(
+                1,
+                &[("MESSAGE", "Started ingest.service"), ("PRIORITY", "6")],
+            ),
+            at(
+                2,
+                &[
+                    ("MESSAGE", "Processed 5000 events"),
+                    ("PRIORITY", "6"),
+                    ("_NAMESPACE", "highvolume"),
+                ],
+            ),
+        ]);
+        let messages = |namespaces: serde_json::Value, default_namespace: bool| -> Vec<String> {
+            let q = build_query(query(json!({
+                "transports": [],
+                "namespaces": namespaces,
+                "defaultNamespace": default_namespace,
+            })))
+            .unwrap();
+            j.query_logs(&q)
+                .unwrap()
+                .rows
+                .into_iter()
+                .map(|r| r[0].clone())
+                .collect()
+        };
+
+        assert_eq!(
+            messages(json!([]), false),
+            vec!["Processed 5000 events", "Started ingest.service"]
+        );
+        assert_eq!(
+            messages(json!(["highvolume"]), false),
+            vec!["Processed 5000 events"]
+        );
+        assert!(messages(json!(["other"]), false).is_empty());
+
+        // The default namespace, alone or with others
+        assert_eq!(messages(json!([]), true), vec!["Started ingest.service"]);
+        assert_eq!(
+            messages(json!(["other"]), true),
+            vec!["Started ingest.service"]
+        );
+        assert_eq!(
+            messages(json!(["highvolume"]), true),
+            vec!["Processed 5000 events", "Started ingest.service"]
+        );
+
+        // Only the queries selecting namespaces read all of the running system
+        let local = JournalSource::Local;
+        let all = JournalSource::Namespace {
+            namespace: "*".into(),
+        };
+        let selecting = |namespaces: serde_json::Value, default_namespace: bool| {
+            let q = build_query(query(json!({
+                "namespaces": namespaces,
+                "defaultNamespace": default_namespace,
+            })))
+            .unwrap();
+            query_source(&local, &q)
+        };
+        assert_eq!(selecting(json!([]), false), local);
+        assert_eq!(selecting(json!([]), true), local);
+        assert_eq!(selecting(json!(["highvolume"]), true), all);
+
+        let directory = JournalSource::Directory {
+            path: "/tmp/journal".into(),
+        };
+        assert_eq!(directory.with_all_namespaces(), directory);
+    }
+
+    #[test]
+    fn filter_matches_agree_with_evaluation() {
+        let entries = vec![
+            at(
+                1,
+                &[("MESSAGE", "type=1130 audit"), ("_TRANSPORT", "audit")],
+            ),
+            at(2, &[("MESSAGE", "Started sshd"), ("PRIORITY", "6")]),
+            at(3, &[("MESSAGE", "Failed password"), ("PRIORITY", "4")]),
+        ];
+        let j = testing::journal(entries.clone());
+
+        for filter in ["PRIORITY!=6", "PRIORITY<6", "PRIORITY=6 OR PRIORITY!=4"] {
+            let q = build_query(query(json!({
+                "priority": 7,
+                "transports": [],
+                "filter": filter,
+            })))
+            .unwrap();
+            let found: Vec<String> = j
+                .query_logs(&q)
+                .unwrap()
+                .rows
+                .into_iter()
+                .map(|r| r[0].clone())
+                .collect();
+
+            let expression = FilterExpression::parse(filter).unwrap();
+            let evaluated: Vec<String> = entries
+                .iter()
+                .rev()
+                .filter(|e| {
+                    expression.matches(&mut |field| {
+                        e.fields
+                            .iter()
+                            .find(|(f, _)| f == field)
+                            .map(|(_, v)| v.clone())
+                    })
+                })
+                .map(|e| e.fields[0].1.clone())
+                .collect();
+
+            assert_eq!(found, evaluated, "{}", filter);
+        }
+    }
+
+    #[test]
+    fn histogram_ranges() {
+        let range = |from: &str, to: &str, bucket_width_seconds: u64| {
+            let query: HistogramQuery = serde_json::from_value(json!({
+                "priority": 6,
+                "datetimeFrom": from,
+                "datetimeTo": to,
+                "bucketWidthSeconds": bucket_width_seconds,
+            }))
+            .unwrap();
+            histogram_range(&query)
+        };
+
+        assert_eq!(
+            range("2024-01-01T00:00:00Z", "2024-01-01T01:00:00Z", 60).unwrap(),
+            (START, START + 3_600_000_000, 60_000_000)
+        );
+
+        for invalid in [
+            range("1969-12-31T00:00:00Z", "2024-01-01T00:00:00Z", 3600),
+            range(
+                "2024-01-01T00:00:00Z",
+                "2024-01-02T00:00:00Z",
+                u64::MAX / 1000,
+            ),
+            range("2024-01-01T00:00:00Z", "2024-01-02T00:00:00Z", 0),
+            range("2024-01-02T00:00:00Z", "2024-01-01T00:00:00Z", 60),
+        ] {
+            assert!(matches!(invalid, Err(JournalError::InvalidQuery(_))));
+        }
+    }
+
+    #[test]
+    fn date_range() {
+        assert_eq!(
+            messages(query(json!({
+                "datetimeFrom": "2024-01-01T00:00:01Z",
+                "datetimeTo": "2024-01-01T00:00:03Z",
+            }))),
+            vec!["Server listening on port 22"]
+        );
+    }
+
+    #[test]
+    fn quick_search_and_cursor() {
+        assert_eq!(
+            messages(query(json!({ "quickSearch": "PORT" }))),
+            vec!["Server listening on port 22"]
+        );
+
+        assert_eq!(
+            messages(query(json!({ "cursor": "c3" }))),
+            vec!["Server listening on port 22", "Reached target Basic System"]
+        );
+
+        assert!(build_query(query(json!({ "quickSearch": "(", "searchMode": "regex" }))).is_err());
+    }
+
+    #[test]
+    fn exact_priorities_and_exclusions() {
+        assert_eq!(
+            messages(query(json!({ "priorities": [4] }))),
+            vec!["Failed password for root"]
+        );
+
+        assert_eq!(
+            messages(query(json!({
+                "priorities": [2, 6],
+                "transports": ["journal", "syslog", "kernel"],
+            }))),
+            vec![
+                "Out of memory: Killed process 815",
+                "Server listening on port 22",
+                "Reached target Basic System"
+            ]
+        );
+
+        assert_eq!(
+            messages(query(json!({ "excludedUnits": ["sshd.service"] }))),
+            vec!["Reached target Basic System"]
+        );
+
+        assert_eq!(
+            messages(query(
+                json!({ "excludedMessages": ["^Server", "(?i)BASIC"] })
+            )),
+            vec!["Failed password for root"]
+        );
+
+        assert!(build_query(query(json!({ "excludedMessages": ["("] }))).is_err());
+    }
+}
@@ -5,6 +5,7 @@
     windows_subsystem = "windows"
 )]
 
+mod export_controller;
 mod journal;
 mod journal_controller;
 mod monitor;
@@ -18,9 +19,12 @@ extern crate lazy_static;
 use std::env;
 use std::str::FromStr;
 
+use crate::journal::Follower;
 use crate::journal::Journal;
 use crate::journal::JournalError;
-use crate::journal::OpenFlags;
+use crate::journal::JournalSource;
+use crate::journal::Sessions;
+use crate::journal_controller::RunningQueries;
 use crate::monitor::Monitor;
 use serde::Deserialize;
 use serde::Serialize;
@@ -40,25 +44,46 @@ fn main() {
         .with(fmt_layer)
         .init();
 
-    let j = Journal::open(
-        OpenFlags::SD_JOURNAL_LOCAL_ONLY
-            | OpenFlags::SD_JOURNAL_SYSTEM
-            | OpenFlags::SD_JOURNAL_CURRENT_USER,
-    )
-    .unwrap();
+    let source = JournalSource::default();
 
     let m = Monitor::new();
 
     info!("Starting journal logger");
     tauri::Builder::default()
-        .manage(Mutex::new(j))
+        .plugin(tauri_plugin_dialog::init())
+        .manage(Mutex::new(Sessions::<Mutex<Journal>>::new(
+            journal_controller::SESSION_IDLE_TIMEOUT,
+        )))
+        .manage(Mutex::new(source))
+        .manage(Mutex::new(Option::<Follower>::None))
+        .manage(Mutex::new(RunningQueries::default()))
         .manage(Mutex::new(m))
         .invoke_handler(tauri::generate_handler![
+            journal_controller::open_session,
+            journal_controller::close_session,
             journal_controller::get_logs,
-            journal_controller::get_summary,
+            journal_controller::get_logs_around,
+            journal_controller::stream_logs,
+            journal_controller::cancel_query,
+            journal_controller::get_histogram,
             journal_controller::get_services,
             journal_controller::get_full_entry,
             journal_controller::get_boots,
+            journal_controller::get_boot_entry_count,
+            journal_controller::get_coredumps,
+            journal_controller::get_coredump_logs,
+            journal_controller::get_audit_events,
+            journal_controller::get_namespaces,
+            journal_controller::get_catalog,
+            journal_controller::get_catalog_text,
+            journal_controller::get_storage_report,
+            journal_controller::get_fields,
+            journal_controller::get_field_values,
+            journal_controller::start_follow,
+            journal_controller::stop_follow,
+            journal_controller::get_journal_source,
+            journal_controller::set_journal_source,
+            export_controller::export_logs,
             monitor_controller::get_system_status,
             monitor_controller::get_processes,
             get_config,
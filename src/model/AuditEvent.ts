@@ -0,0 +1,48 @@
+export type AuditRecord = {
+  recordType: string;
+  serial: number | null;
+  timestamp: number | null;
+  fields: [string, string][];
+};
+
+export type AvcDetails = {
+  type: "avc";
+  result: string;
+  permissions: string[];
+  pid: number | null;
+  comm: string | null;
+  name: string | null;
+  source: string | null;
+  target: string | null;
+  targetClass: string | null;
+};
+
+export type ExecveDetails = {
+  type: "execve";
+  argv: string[];
+  exe: string | null;
+  cwd: string | null;
+  pid: number | null;
+  uid: number | null;
+  auid: number | null;
+};
+
+export type LoginDetails = {
+  type: "login";
+  recordType: string;
+  account: string | null;
+  exe: string | null;
+  hostname: string | null;
+  address: string | null;
+  terminal: string | null;
+  success: boolean;
+};
+
+export type AuditDetails = AvcDetails | ExecveDetails | LoginDetails | { type: "other" };
+
+export type AuditEvent = {
+  serial: number;
+  timestamp: number;
+  details: AuditDetails;
+  records: AuditRecord[];
+};
@@ -3,4 +3,7 @@ export type Boot = {
   boot_id: string;
   first_entry: number;
   last_entry: number;
+  entry_count: number | null;
+  kernel_version: string | null;
+  hostname: string | null;
 };
@@ -0,0 +1,5 @@
+export type CatalogEntry = {
+  messageId: string;
+  component: string | null;
+  subject: string;
+};
@@ -0,0 +1,13 @@
+export type Coredump = {
+  cursor: string;
+  realtime: number;
+  bootId: string;
+  pid: number | null;
+  uid: number | null;
+  exe: string;
+  signal: number | null;
+  signalName: string;
+  unit: string | null;
+  userUnit: string | null;
+  stackTrace: string | null;
+};
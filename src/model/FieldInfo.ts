@@ -0,0 +1,4 @@
+export type FieldInfo = {
+  name: string;
+  description: string | null;
+};
@@ -1,8 +1,13 @@
 export type Filter = {
   priority: string;
   services: string[];
+  userServices: string[];
   transports: string[];
   datetimeFrom: string;
   datetimeTo: string;
   bootIds: string[];
+  namespaces: string[];
+  defaultNamespace: boolean;
+  field: string;
+  fieldValues: string[];
 };
@@ -0,0 +1,12 @@
+export type HistogramBucket = {
+  start: number;
+  total: number;
+  priorities: Array<number>;
+};
+
+export type Histogram = {
+  from: number;
+  to: number;
+  bucketWidth: number;
+  buckets: Array<HistogramBucket>;
+};
@@ -3,7 +3,15 @@ export type JournalEntries = {
   rows: Array<Array<string>>;
 };
 
+// Entries around an anchor, with the tokens to continue in either direction
+export type JournalPage = JournalEntries & {
+  anchor: number | null;
+  newer: string | null;
+  older: string | null;
+};
+
 export type JournalEntry = {
   headers: Array<string>;
   values: Array<string>;
+  catalog: string | null;
 };
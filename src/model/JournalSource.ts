@@ -0,0 +1,7 @@
+// Where the journal entries are read from, switched for all the views at once
+export type JournalSource =
+  | { type: "local" }
+  | { type: "namespace"; namespace: string }
+  | { type: "directory"; path: string }
+  | { type: "files"; paths: string[] }
+  | { type: "import"; path: string };
@@ -0,0 +1,7 @@
+// Values of the same field are ORed, the fields ANDed
+export type KernelFilter = {
+  subsystems: string[];
+  devices: string[];
+  sysnames: string[];
+  devnodes: string[];
+};
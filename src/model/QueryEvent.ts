@@ -0,0 +1,13 @@
+import type { JournalEntries } from "./JournalEntries";
+
+export type QueryProgress = {
+  scanned: number;
+  matched: number;
+  timestamp: number;
+  cancelled: boolean;
+};
+
+export type QueryEvent =
+  | { event: "rows"; data: JournalEntries }
+  | { event: "progress"; data: QueryProgress }
+  | { event: "finished"; data: QueryProgress };
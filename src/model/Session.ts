@@ -0,0 +1,2 @@
+// Runs a command on the journal session of the view, reopening it if it expired
+export type WithSession = <T>(call: (sessionId: number) => Promise<T>) => Promise<T>;
@@ -0,0 +1,37 @@
+export type JournalFileInfo = {
+  path: string;
+  size: number;
+  state: "online" | "offline" | "archived" | "corrupt";
+  error: string | null;
+  machineId: string | null;
+  entries: number | null;
+  firstEntry: number | null;
+  lastEntry: number | null;
+  firstSeqnum: number | null;
+  lastSeqnum: number | null;
+  compression: string | null;
+  compact: boolean;
+};
+
+export type StorageLimits = {
+  maxUse: number | null;
+  keepFree: number | null;
+  maxFileSize: number | null;
+  maxFiles: number;
+};
+
+export type JournaldConfig = {
+  files: string[];
+  storage: string;
+  compress: string;
+  system: StorageLimits;
+  runtime: StorageLimits;
+  maxRetentionSec: number | null;
+  maxFileSec: number | null;
+};
+
+export type StorageReport = {
+  usage: number;
+  files: JournalFileInfo[];
+  config: JournaldConfig | null;
+};
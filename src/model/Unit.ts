@@ -2,4 +2,9 @@ export type Unit = {
   unit_file: string;
   state: string;
   preset: string;
+  unit_type: UnitType;
+  // Unit of the user's service manager
+  user: boolean;
 };
+
+export type UnitType = "service" | "scope" | "timer" | "socket" | "mount";